@@ -0,0 +1,178 @@
+//! Benchmarks parsing and each part of every day on its `real` input, and compares the medians
+//! against the timings stored in `benches/baseline.txt`.
+//!
+//! ```text
+//! cargo bench                          # compare every day against the baseline
+//! cargo bench -- day22 2023/day23      # only the named days
+//! cargo bench -- --save-baseline       # record this run as the new baseline
+//! ```
+//!
+//! Exits non-zero if any timing is slower than its baseline by more than [`THRESHOLD`]. The
+//! baseline is only meaningful on the machine it was recorded on, so re-save it before comparing
+//! on a different one.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use aoc23::days::{DayId, DAYS, DEFAULT_YEAR};
+use aoc23::inputs::{self, INPUTS_DIR};
+use aoc23::runner::{self, ParseFailure, Parts, Report, Stats};
+
+const BASELINE: &str = "benches/baseline.txt";
+
+/// How much slower than the baseline a median may be before it's flagged.
+const THRESHOLD: f64 = 0.25;
+/// Differences smaller than this are treated as noise, whatever the ratio.
+const NOISE: Duration = Duration::from_millis(1);
+
+const MIN_RUNS: usize = 3;
+const MAX_RUNS: usize = 50;
+const TARGET_TIME: Duration = Duration::from_secs(1);
+
+const STAGES: [&str; 3] = ["parse", "part1", "part2"];
+
+fn bench(parse: aoc23::days::ParseFn, input: &str) -> Result<Report, ParseFailure> {
+    let start = Instant::now();
+    let mut timings = Vec::new();
+    let mut answers = None;
+    while timings.len() < MIN_RUNS || (timings.len() < MAX_RUNS && start.elapsed() < TARGET_TIME) {
+        let (a, t) = runner::run(parse, input, Parts::Both)?;
+        timings.push(t);
+        answers = Some(a);
+    }
+    Ok(Report {
+        answers: answers.unwrap(),
+        timings,
+    })
+}
+
+fn load_baseline() -> BTreeMap<(String, String), Duration> {
+    let mut baseline = BTreeMap::<(String, String), Duration>::new();
+    let Ok(contents) = std::fs::read_to_string(BASELINE) else {
+        return baseline;
+    };
+    for line in contents.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        let [day, stage, nanos] = parts[..] else {
+            panic!("bad baseline line {line}");
+        };
+        let nanos = nanos.parse::<u64>().unwrap();
+        baseline.insert(
+            (day.to_owned(), stage.to_owned()),
+            Duration::from_nanos(nanos),
+        );
+    }
+    baseline
+}
+
+fn save_baseline(medians: &BTreeMap<(String, String), Duration>) {
+    let mut contents = String::from(
+        "# day stage median-nanoseconds, written by `cargo bench -- --save-baseline`\n",
+    );
+    for day in DAYS.iter().map(|d| d.0.to_string()) {
+        for stage in STAGES {
+            if let Some(median) = medians.get(&(day.to_owned(), stage.to_owned())) {
+                contents += &format!("{day} {stage} {}\n", median.as_nanos());
+            }
+        }
+    }
+    std::fs::write(BASELINE, contents).unwrap();
+}
+
+fn main() {
+    let mut save = false;
+    let mut only = Vec::<DayId>::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--save-baseline" => save = true,
+            // Passed by `cargo bench` to every bench target.
+            "--bench" => {}
+            other => only.push(
+                DayId::parse(other, DEFAULT_YEAR).unwrap_or_else(|| panic!("unknown day {other}")),
+            ),
+        }
+    }
+
+    let baseline = load_baseline();
+    let mut medians = BTreeMap::<(String, String), Duration>::new();
+    let mut regressions = 0usize;
+    let mut failed = 0usize;
+
+    println!(
+        "{:<10} {:<6} {:>12} {:>12} {:>8} {:>5}",
+        "Day", "Stage", "Median", "Baseline", "Change", "Runs"
+    );
+    for (day, parse) in DAYS {
+        if !only.is_empty() && !only.contains(day) {
+            continue;
+        }
+        let name = day.to_string();
+        let path = inputs::input_path(Path::new(INPUTS_DIR), *day, "real");
+        let Ok(input) = std::fs::read_to_string(&path) else {
+            println!("{name:<10} no input at {}", path.display());
+            continue;
+        };
+
+        let report = match bench(*parse, &input) {
+            Ok(report) => report,
+            Err(e) => {
+                failed += 1;
+                println!("{name:<10} failed: {}", e.in_file(&name, &path));
+                continue;
+            }
+        };
+        if !report.answers.is_ok() {
+            failed += 1;
+            println!("{name:<10} failed: {:?}", report.answers);
+            continue;
+        }
+
+        let stats: [Stats; 3] = [
+            report.parse_stats(),
+            report.part1_stats(),
+            report.part2_stats(),
+        ];
+        for (stage, stats) in STAGES.iter().zip(stats) {
+            let key = (name.to_string(), stage.to_string());
+            let (base, change, flag) = match baseline.get(&key) {
+                Some(base) => {
+                    let ratio = stats.median.as_secs_f64() / base.as_secs_f64() - 1.0;
+                    let regressed = ratio > THRESHOLD && stats.median.saturating_sub(*base) > NOISE;
+                    if regressed {
+                        regressions += 1;
+                    }
+                    (
+                        format!("{base:.2?}"),
+                        format!("{:+.0}%", ratio * 100.0),
+                        if regressed { "  REGRESSED" } else { "" },
+                    )
+                }
+                None => ("-".to_owned(), "-".to_owned(), ""),
+            };
+            println!(
+                "{:<10} {:<6} {:>12} {:>12} {:>8} {:>5}{flag}",
+                name,
+                stage,
+                format!("{:.2?}", stats.median),
+                base,
+                change,
+                report.timings.len()
+            );
+            medians.insert(key, stats.median);
+        }
+    }
+
+    if save {
+        let mut merged = baseline;
+        merged.extend(medians);
+        save_baseline(&merged);
+        println!("saved baseline to {BASELINE}");
+    } else if regressions > 0 || failed > 0 {
+        eprintln!("{regressions} regression(s), {failed} failure(s)");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The year of the days directly under `src/days` and of the 2023 inputs, as
+/// `days::DEFAULT_YEAR`.
+const DEFAULT_YEAR: u32 = 2023;
+
+/// The day number in a name like `day7`, with whatever `suffix` follows it.
+fn day_number(name: &str, suffix: &str) -> Option<u32> {
+    let n = name.strip_prefix("day")?.strip_suffix(suffix)?;
+    n.parse::<u32>().ok()
+}
+
+/// How many times `source` implements `Solution` for `DayN`, going by its items, however the trait
+/// and type are written. `None` if it doesn't parse, which rustc will say more about.
+fn solution_impls(source: &str, n: u32) -> Option<usize> {
+    let file = syn::parse_file(source).ok()?;
+    let day = format!("Day{n}");
+    let last_is =
+        |path: &syn::Path, name: &str| path.segments.last().is_some_and(|s| s.ident == name);
+    let impls = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Impl(i) => Some(i),
+            _ => None,
+        })
+        .filter(|i| {
+            i.trait_
+                .as_ref()
+                .is_some_and(|(_, path, _)| last_is(path, "Solution"))
+        })
+        .filter(|i| matches!(&*i.self_ty, syn::Type::Path(t) if last_is(&t.path, &day)))
+        .count();
+    Some(impls)
+}
+
+/// The years with a directory of their own under `dir`, like `2024`, in order.
+fn year_dirs(dir: &Path) -> Vec<u32> {
+    let mut years = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.is_dir())
+        .filter_map(|p| p.file_name()?.to_str()?.parse::<u32>().ok())
+        .collect::<Vec<_>>();
+    years.sort();
+    years
+}
+
+/// Generates the `mod` declarations and the `DAYS` and `GENERATORS` registries for every
+/// `dayN.rs` in `days`, and in its year directories, in year and day order. A file that doesn't
+/// implement `Solution` for its own `DayN`, or two files for the same day, become
+/// `compile_error!`s naming the files.
+fn write_day_registry(days: &Path, out: &Path) {
+    let mut errors = Vec::<String>::new();
+    let mut dirs = vec![(DEFAULT_YEAR, days.to_path_buf(), "src/days".to_owned())];
+    for year in year_dirs(days) {
+        if year == DEFAULT_YEAR {
+            errors.push(format!(
+                "days from {year} go directly in src/days, not src/days/{year}"
+            ));
+            continue;
+        }
+        dirs.push((
+            year,
+            days.join(year.to_string()),
+            format!("src/days/{year}"),
+        ));
+    }
+
+    let mut registered = Vec::<(u32, u32, String)>::new();
+    for (year, dir, shown) in dirs {
+        let mut found = BTreeMap::<u32, Vec<String>>::new();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            if let Some(n) = day_number(&name, ".rs") {
+                found.entry(n).or_default().push(name);
+            }
+        }
+
+        for (n, mut names) in found {
+            names.sort();
+            if names.len() > 1 {
+                let names = names
+                    .iter()
+                    .map(|f| format!("{shown}/{f}"))
+                    .collect::<Vec<_>>();
+                errors.push(format!(
+                    "day {n} of {year} is defined by more than one file: {}",
+                    names.join(", ")
+                ));
+                continue;
+            }
+            let name = names.pop().unwrap();
+            let source = std::fs::read_to_string(dir.join(&name)).unwrap();
+            let impls = solution_impls(&source, n);
+            if impls == Some(0) {
+                errors.push(format!(
+                    "{shown}/{name} doesn't implement `Solution` for `Day{n}`"
+                ));
+            } else if let Some(impls @ 2..) = impls {
+                errors.push(format!(
+                    "{shown}/{name} implements `Solution` {impls} times"
+                ));
+            } else {
+                // The registry is included from `OUT_DIR`, so `#[path]` has to point back at the source
+                let path = dir.join(&name).to_str().unwrap().to_owned();
+                registered.push((year, n, path));
+            }
+        }
+    }
+
+    let module = |year: u32, n: u32| {
+        if year == DEFAULT_YEAR {
+            format!("day{n}")
+        } else {
+            format!("y{year}::day{n}")
+        }
+    };
+    let mut registry = String::new();
+    for e in errors {
+        writeln!(registry, "compile_error!({e:?});").unwrap();
+    }
+    let mut open_year = None;
+    for (year, n, path) in &registered {
+        if *year != DEFAULT_YEAR && open_year != Some(*year) {
+            if open_year.is_some() {
+                writeln!(registry, "}}").unwrap();
+            }
+            // The days' `use super::...` then finds what they would in `days` itself
+            writeln!(
+                registry,
+                "pub mod y{year} {{\n#[allow(unused_imports)]\nuse super::*;"
+            )
+            .unwrap();
+            open_year = Some(*year);
+        }
+        writeln!(registry, "#[path = {path:?}]\npub mod day{n};").unwrap();
+    }
+    if open_year.is_some() {
+        writeln!(registry, "}}").unwrap();
+    }
+    writeln!(registry, "\npub const DAYS: &[(DayId, ParseFn)] = &[").unwrap();
+    for (year, n, _) in &registered {
+        let module = module(*year, *n);
+        writeln!(
+            registry,
+            "    (DayId::new({year}, {n}), parse::<{module}::Day{n}>),"
+        )
+        .unwrap();
+    }
+    writeln!(registry, "];").unwrap();
+    writeln!(
+        registry,
+        "\npub const GENERATORS: &[(DayId, GenerateFn)] = &["
+    )
+    .unwrap();
+    for (year, n, _) in &registered {
+        let module = module(*year, *n);
+        writeln!(
+            registry,
+            "    (DayId::new({year}, {n}), {module}::Day{n}::generate),"
+        )
+        .unwrap();
+    }
+    writeln!(registry, "];").unwrap();
+
+    std::fs::write(out.join("days.rs"), registry).unwrap();
+}
+
+/// Generates one test per input with a recorded `.answers` file, grouped into a module per year
+/// and day.
+fn write_input_tests(inputs: &Path, out: &Path) {
+    let mut tests = String::new();
+    for year in year_dirs(inputs) {
+        let mut days = std::fs::read_dir(inputs.join(year.to_string()))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.is_dir())
+            .filter_map(|p| Some((day_number(p.file_name()?.to_str()?, "")?, p)))
+            .collect::<Vec<_>>();
+        days.sort();
+
+        writeln!(tests, "mod y{year} {{").unwrap();
+        for (n, day_dir) in days {
+            let mut variants = std::fs::read_dir(&day_dir)
+                .unwrap()
+                .map(|e| e.unwrap().path())
+                .filter(|p| p.extension().is_some_and(|e| e == "answers"))
+                .filter(|p| p.with_extension("txt").exists())
+                .map(|p| p.file_stem().unwrap().to_str().unwrap().to_owned())
+                .collect::<Vec<_>>();
+            if variants.is_empty() {
+                continue;
+            }
+            variants.sort();
+
+            writeln!(tests, "    mod day{n} {{").unwrap();
+            for variant in variants {
+                if variant == "real" {
+                    // Real inputs take minutes for some days in debug builds; `aoc23 verify` or
+                    // `cargo test --release -- --ignored` covers them.
+                    writeln!(tests, "        #[ignore = \"real input\"]").unwrap();
+                }
+                writeln!(tests, "        #[test]").unwrap();
+                writeln!(
+                    tests,
+                    "        fn {variant}() {{ crate::check(aoc23::days::DayId::new({year}, {n}), \"{variant}\"); }}"
+                )
+                .unwrap();
+            }
+            writeln!(tests, "    }}").unwrap();
+        }
+        writeln!(tests, "}}").unwrap();
+    }
+
+    std::fs::write(out.join("input_tests.rs"), tests).unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=inputs");
+    println!("cargo:rerun-if-changed=src/days");
+    let out = std::env::var("OUT_DIR").unwrap();
+    let manifest = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    write_day_registry(&Path::new(&manifest).join("src/days"), Path::new(&out));
+    write_input_tests(Path::new("inputs"), Path::new(&out));
+}
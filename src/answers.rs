@@ -0,0 +1,151 @@
+//! Side-car files recording the known answers for an input.
+//!
+//! `inputs/2023/day7/real.answers` holds the answers for `inputs/2023/day7/real.txt`:
+//!
+//! ```text
+//! part1: 248812215
+//! part2: 250057090
+//! ```
+//!
+//! Either line may be left out when that part has no meaningful answer for the input, e.g. a
+//! part 2 example run through part 1. Blank lines and lines starting with `#` are ignored.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::days::{DayId, ParseFn};
+use crate::runner::{self, Answers, Parts};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expected {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// A part whose answer didn't match what was expected.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    pub part: usize,
+    pub expected: String,
+    pub got: Result<String, String>,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.got {
+            Ok(got) => write!(
+                f,
+                "part {}: expected {}, got {}",
+                self.part, self.expected, got
+            ),
+            Err(e) => write!(
+                f,
+                "part {}: expected {}, failed: {}",
+                self.part, self.expected, e
+            ),
+        }
+    }
+}
+
+pub fn answers_path(input_path: &Path) -> PathBuf {
+    input_path.with_extension("answers")
+}
+
+impl Expected {
+    pub fn parse(s: &str) -> Result<Expected, String> {
+        let mut expected = Expected::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("line {}: expected `partN: answer`", i + 1))?;
+            let value = Some(value.trim().to_owned());
+            match key.trim() {
+                "part1" => expected.part1 = value,
+                "part2" => expected.part2 = value,
+                other => return Err(format!("line {}: unknown part `{other}`", i + 1)),
+            }
+        }
+        Ok(expected)
+    }
+
+    /// Loads the answers recorded alongside `input_path`, if there are any.
+    pub fn load(input_path: &Path) -> Result<Option<Expected>, String> {
+        let path = answers_path(input_path);
+        match std::fs::read_to_string(&path) {
+            Ok(s) => Expected::parse(&s)
+                .map(Some)
+                .map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    /// Compares the recorded answers against `answers`, skipping any part without a recorded answer
+    /// or that wasn't run.
+    pub fn check(&self, answers: &Answers) -> Vec<Mismatch> {
+        let mut mismatches = Vec::<Mismatch>::new();
+        for (part, expected, got) in [
+            (1, &self.part1, &answers.part1),
+            (2, &self.part2, &answers.part2),
+        ] {
+            if let (Some(expected), Some(got)) = (expected, got) {
+                if got.as_ref() != Ok(expected) {
+                    mismatches.push(Mismatch {
+                        part,
+                        expected: expected.clone(),
+                        got: got.clone(),
+                    });
+                }
+            }
+        }
+        mismatches
+    }
+}
+
+/// What came of running a day against an input and comparing with its recorded answers.
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    /// How long parsing and the parts took, or `None` if the input couldn't be parsed.
+    pub elapsed: Option<Duration>,
+    /// Why the input couldn't be parsed, or each part whose answer didn't match.
+    pub problems: Vec<String>,
+}
+
+impl Verdict {
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Runs `day` against the input at `path` and checks the answers recorded alongside it, or
+/// returns `None` if there aren't any.
+pub fn verify(
+    day: DayId,
+    parse: ParseFn,
+    path: &Path,
+    parts: Parts,
+) -> Result<Option<Verdict>, String> {
+    let Some(expected) = Expected::load(path)? else {
+        return Ok(None);
+    };
+    let input = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let verdict = match runner::run(parse, &input, parts) {
+        Ok((answers, timings)) => Verdict {
+            elapsed: Some(timings.parse + timings.part1 + timings.part2),
+            problems: expected
+                .check(&answers)
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+        },
+        Err(e) => Verdict {
+            elapsed: None,
+            problems: vec![e.in_file(&day.to_string(), path).to_string()],
+        },
+    };
+    Ok(Some(verdict))
+}
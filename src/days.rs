@@ -1,53 +1,185 @@
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-
-pub const DAYS: &[(&'static str, fn(&str) -> (String, String))] = &[
-    ("day1", day1::main),
-    ("day2", day2::main),
-    ("day3", day3::main),
-    ("day4", day4::main),
-    ("day5", day5::main),
-    ("day6", day6::main),
-    ("day7", day7::main),
-    ("day8", day8::main),
-    ("day9", day9::main),
-    ("day10", day10::main),
-    ("day11", day11::main),
-    ("day12", day12::main),
-    ("day13", day13::main),
-    ("day14", day14::main),
-    ("day15", day15::main),
-    ("day16", day16::main),
-    ("day17", day17::main),
-    ("day18", day18::main),
-    ("day19", day19::main),
-    ("day20", day20::main),
-    ("day21", day21::main),
-    ("day22", day22::main),
-    ("day23", day23::main),
-    ("day24", day24::main),
-    ("day25", day25::main),
+use std::fmt::Display;
+
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::render::{Palette, Rgb};
+
+// `pub mod dayN;` for every `days/dayN.rs`, `pub mod yYYYY { pub mod dayN; }` for every
+// `days/YYYY/dayN.rs`, and the `DAYS` and `GENERATORS` registries of them in year and day order
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+/// The year that the days directly under `days/` are from. Other years' days live in
+/// `days/<year>/`, and their inputs in `inputs/<year>/` alongside this year's.
+pub const DEFAULT_YEAR: u32 = 2023;
+
+/// Which puzzle a day is: the year it was set in and its day of the advent calendar.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DayId {
+    pub year: u32,
+    pub day: u32,
+}
+
+impl DayId {
+    pub const fn new(year: u32, day: u32) -> DayId {
+        DayId { year, day }
+    }
+
+    /// The day's name within its year, like `day7`, as its module and inputs directory are named.
+    pub fn name(&self) -> String {
+        format!("day{}", self.day)
+    }
+
+    /// Reads `7`, `day7` or `2024/day7`, taking the day to be in `year` unless it says otherwise.
+    pub fn parse(s: &str, year: u32) -> Option<DayId> {
+        let (year, day) = match s.split_once('/') {
+            Some((y, day)) => (y.parse::<u32>().ok()?, day),
+            None => (year, s),
+        };
+        let day = day.strip_prefix("day").unwrap_or(day);
+        if day.starts_with(['0', '+']) {
+            return None;
+        }
+        day.parse::<u32>()
+            .ok()
+            .filter(|&d| d > 0)
+            .map(|d| DayId::new(year, d))
+    }
+}
+
+impl Display for DayId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/day{}", self.year, self.day)
+    }
+}
+
+/// A day's puzzle, split into parsing the input once and solving each part against the parsed model.
+pub trait Solution {
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Answer1;
+    fn part2(input: &Self::Input) -> Self::Answer2;
+
+    /// A made up input for the parser to read, about `size` big in whatever the puzzle is
+    /// counted in, or about as big as the real input if `size` is `None`.
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String;
+
+    /// A picture of the puzzle, for days with something worth looking at.
+    fn render(_input: &Self::Input, _palette: &Palette) -> Option<Grid<Rgb>> {
+        None
+    }
+
+    /// The answers that rest on shortcuts, each next to the answer from a slow but simple
+    /// reference solver, for days whose shortcuts only hold for inputs like the real one.
+    fn cross_check(_input: &Self::Input) -> Vec<Comparison> {
+        Vec::new()
+    }
+}
+
+/// An answer that some inputs don't have, like a step count to a node that isn't there, shown as
+/// `None` for those.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaybeAnswer<T>(pub Option<T>);
+
+impl<T: Display> Display for MaybeAnswer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(answer) => write!(f, "{answer}"),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+/// An answer worked out by a day's shortcut and again by brute force.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// What was worked out, like `part 2`.
+    pub what: String,
+    pub fast: String,
+    /// `None` if brute force would have taken too long.
+    pub reference: Option<String>,
+}
+
+impl Comparison {
+    pub fn new(
+        what: impl Display,
+        fast: impl Display,
+        reference: Option<impl Display>,
+    ) -> Comparison {
+        Comparison {
+            what: what.to_string(),
+            fast: fast.to_string(),
+            reference: reference.map(|r| r.to_string()),
+        }
+    }
+
+    /// Whether the answers match, or there's no reference answer to go against.
+    pub fn agrees(&self) -> bool {
+        self.reference.as_ref().is_none_or(|r| *r == self.fast)
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reference {
+            Some(r) => write!(
+                f,
+                "{}: {} from the solver, {r} from the reference",
+                self.what, self.fast
+            ),
+            None => write!(
+                f,
+                "{}: {} from the solver, too slow to check",
+                self.what, self.fast
+            ),
+        }
+    }
+}
+
+/// A parsed input with the day it belongs to erased, so the runner can drive any day.
+pub trait Parsed {
+    fn part1(&self) -> String;
+    fn part2(&self) -> String;
+    fn render(&self, palette: &Palette) -> Option<Grid<Rgb>>;
+    fn cross_check(&self) -> Vec<Comparison>;
+}
+
+struct ParsedInput<S: Solution>(S::Input);
+
+impl<S: Solution> Parsed for ParsedInput<S> {
+    fn part1(&self) -> String {
+        S::part1(&self.0).to_string()
+    }
+
+    fn part2(&self) -> String {
+        S::part2(&self.0).to_string()
+    }
+
+    fn render(&self, palette: &Palette) -> Option<Grid<Rgb>> {
+        S::render(&self.0, palette)
+    }
+
+    fn cross_check(&self) -> Vec<Comparison> {
+        S::cross_check(&self.0)
+    }
+}
+
+fn parse<S: Solution + 'static>(input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+    Ok(Box::new(ParsedInput::<S>(S::parse(input)?)))
+}
+
+pub type ParseFn = fn(&str) -> Result<Box<dyn Parsed>, ParseError>;
+
+pub type GenerateFn = fn(&mut Rng, Option<usize>) -> String;
+
+/// The days with reference solvers to check their shortcuts against, with an input size small
+/// enough for brute force.
+pub const CROSS_CHECKED: &[(DayId, usize)] = &[
+    (DayId::new(2023, 5), 4),
+    (DayId::new(2023, 8), 3),
+    (DayId::new(2023, 20), 4),
+    (DayId::new(2023, 21), 11),
 ];
@@ -1,4 +1,8 @@
-const TOKENS: &[(&'static str, u32)] = &[
+use super::{MaybeAnswer, Solution};
+use crate::generate::{Rng, LOWERCASE};
+use crate::parse::ParseError;
+
+const TOKENS: &[(&str, u32)] = &[
     ("1", 1),
     ("2", 2),
     ("3", 3),
@@ -19,39 +23,48 @@ const TOKENS: &[(&'static str, u32)] = &[
     ("nine", 9),
 ];
 
-pub fn main(input: &str) -> (String, String) {
-    let first: Option<u32> = || -> Option<u32> {
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<String>;
+    type Answer1 = MaybeAnswer<u32>;
+    type Answer2 = MaybeAnswer<u32>;
+
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        Ok(input.lines().map(|l| l.to_owned()).collect())
+    }
+
+    fn part1(lines: &Vec<String>) -> MaybeAnswer<u32> {
         let mut cal_vals = Vec::<u32>::new();
 
-        for line in input.lines() {
+        for line in lines.iter() {
             let mut first_digit: Option<u32> = None;
             let mut last_digit: Option<u32> = None;
             for c in line.chars() {
-                match c.to_digit(10) {
-                    Some(c) => {
-                        if first_digit.is_none() {
-                            first_digit = Some(c);
-                        }
-                        last_digit = Some(c)
+                if let Some(c) = c.to_digit(10) {
+                    if first_digit.is_none() {
+                        first_digit = Some(c);
                     }
-                    None => {}
+                    last_digit = Some(c)
                 }
             }
-            let concat: String = format!("{}{}", first_digit?, last_digit?);
-            cal_vals.push(concat.parse::<u32>().ok()?);
+            let (Some(first_digit), Some(last_digit)) = (first_digit, last_digit) else {
+                return MaybeAnswer(None);
+            };
+            cal_vals.push(first_digit * 10 + last_digit);
         }
-        Some(cal_vals.iter().sum())
-    }();
+        MaybeAnswer(Some(cal_vals.iter().sum()))
+    }
 
-    let second: Option<u32> = || -> Option<u32> {
+    fn part2(lines: &Vec<String>) -> MaybeAnswer<u32> {
         let mut cal_vals = Vec::<u32>::new();
 
-        for line in input.lines() {
+        for line in lines.iter() {
             let mut first_digit: Option<(usize, u32)> = None;
             let mut last_digit: Option<(usize, u32)> = None;
             for token in TOKENS {
-                match line.find(token.0) {
-                    Some(idx) => match first_digit {
+                if let Some(idx) = line.find(token.0) {
+                    match first_digit {
                         Some((old_idx, _)) => {
                             if idx < old_idx {
                                 first_digit = Some((idx, token.1));
@@ -60,11 +73,10 @@ pub fn main(input: &str) -> (String, String) {
                         None => {
                             first_digit = Some((idx, token.1));
                         }
-                    },
-                    None => {}
+                    }
                 }
-                match line.rfind(token.0) {
-                    Some(idx) => match last_digit {
+                if let Some(idx) = line.rfind(token.0) {
+                    match last_digit {
                         Some((old_idx, _)) => {
                             if idx > old_idx {
                                 last_digit = Some((idx, token.1));
@@ -73,15 +85,38 @@ pub fn main(input: &str) -> (String, String) {
                         None => {
                             last_digit = Some((idx, token.1));
                         }
-                    },
-                    None => {}
+                    }
                 }
             }
-            let concat: String = format!("{}{}", first_digit?.1, last_digit?.1);
-            cal_vals.push(concat.parse::<u32>().ok()?);
+            let (Some((_, first_digit)), Some((_, last_digit))) = (first_digit, last_digit) else {
+                return MaybeAnswer(None);
+            };
+            cal_vals.push(first_digit * 10 + last_digit);
         }
-        Some(cal_vals.iter().sum())
-    }();
+        MaybeAnswer(Some(cal_vals.iter().sum()))
+    }
 
-    (format!("{first:?}"), format!("{second:?}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let mut out = String::new();
+        for _ in 0..size.unwrap_or(1000) {
+            let mut line = String::new();
+            for _ in 0..rng.range(1..=6) {
+                match rng.below(3) {
+                    0 => {
+                        let len = rng.range(1..=5);
+                        line += &rng.string(LOWERCASE, len);
+                    }
+                    1 => line += rng.pick(TOKENS).0,
+                    _ => line += &rng.range(1..=9).to_string(),
+                }
+            }
+            if !line.chars().any(|c| c.is_ascii_digit()) {
+                let at = rng.below(line.len() + 1);
+                line.insert_str(at, &rng.range(1..=9).to_string());
+            }
+            out += &line;
+            out.push('\n');
+        }
+        out
+    }
 }
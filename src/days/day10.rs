@@ -1,7 +1,14 @@
 use std::fmt::Display;
 
+use super::Solution;
+use crate::dir::{Dir, Pos};
+use crate::generate::{self, Rng};
+use crate::grid::{self, Grid};
+use crate::parse::ParseError;
+use crate::render::{Palette, Rgb};
+
 #[derive(Copy, Clone, Debug)]
-enum Tile {
+pub enum Tile {
     NS,
     EW,
     NE,
@@ -12,83 +19,192 @@ enum Tile {
     AN,
 }
 
-struct Tiles {
-    rows: Vec<Vec<Tile>>,
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Tile::NS => '║',
+            Tile::EW => '═',
+            Tile::NE => '╚',
+            Tile::NW => '╝',
+            Tile::SW => '╗',
+            Tile::SE => '╔',
+            Tile::GR => ' ',
+            Tile::AN => '?',
+        };
+        std::fmt::Write::write_char(f, c)
+    }
 }
 
-impl Display for Tiles {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.rows.iter() {
-            for tile in row.iter() {
-                let c = match tile {
-                    Tile::NS => '║',
-                    Tile::EW => '═',
-                    Tile::NE => '╚',
-                    Tile::NW => '╝',
-                    Tile::SW => '╗',
-                    Tile::SE => '╔',
-                    Tile::GR => ' ',
-                    Tile::AN => '?',
+fn to_dir(t: Tile, from_dir: Dir) -> Option<Dir> {
+    match (t, from_dir) {
+        (Tile::NS, Dir::Up) => Some(Dir::Down),
+        (Tile::NS, Dir::Right) => None,
+        (Tile::NS, Dir::Down) => Some(Dir::Up),
+        (Tile::NS, Dir::Left) => None,
+        (Tile::EW, Dir::Up) => None,
+        (Tile::EW, Dir::Right) => Some(Dir::Left),
+        (Tile::EW, Dir::Down) => None,
+        (Tile::EW, Dir::Left) => Some(Dir::Right),
+        (Tile::NE, Dir::Up) => Some(Dir::Right),
+        (Tile::NE, Dir::Right) => Some(Dir::Up),
+        (Tile::NE, Dir::Down) => None,
+        (Tile::NE, Dir::Left) => None,
+        (Tile::NW, Dir::Up) => Some(Dir::Left),
+        (Tile::NW, Dir::Right) => None,
+        (Tile::NW, Dir::Down) => None,
+        (Tile::NW, Dir::Left) => Some(Dir::Up),
+        (Tile::SW, Dir::Up) => None,
+        (Tile::SW, Dir::Right) => None,
+        (Tile::SW, Dir::Down) => Some(Dir::Left),
+        (Tile::SW, Dir::Left) => Some(Dir::Down),
+        (Tile::SE, Dir::Up) => None,
+        (Tile::SE, Dir::Right) => Some(Dir::Down),
+        (Tile::SE, Dir::Down) => Some(Dir::Right),
+        (Tile::SE, Dir::Left) => None,
+        (Tile::GR, Dir::Up) => None,
+        (Tile::GR, Dir::Right) => None,
+        (Tile::GR, Dir::Down) => None,
+        (Tile::GR, Dir::Left) => None,
+        (Tile::AN, Dir::Up) => None,
+        (Tile::AN, Dir::Right) => None,
+        (Tile::AN, Dir::Down) => None,
+        (Tile::AN, Dir::Left) => None,
+    }
+}
+
+/// The loop through `start`, and the tile `start` stands for, if it's on one.
+fn get_path(t: &Grid<Tile>, start: (usize, usize)) -> Option<(Vec<(usize, usize)>, Tile)> {
+    assert!(matches!(t[start], Tile::AN));
+    for possible_tile in [Tile::NS, Tile::EW, Tile::NE, Tile::NW, Tile::SW, Tile::SE].iter() {
+        for possible_dir in Dir::ALL {
+            let mut pos = start;
+            let mut tile = possible_tile;
+            let mut from_dir = possible_dir;
+            let mut pos_history = Vec::<(usize, usize)>::new();
+            loop {
+                let next_dir = match to_dir(*tile, from_dir) {
+                    None => break,
+                    Some(d) => d,
                 };
-                std::fmt::Write::write_char(f, c)?;
+                let Some(next_pos) = t.step(pos, next_dir) else {
+                    break;
+                };
+                let next_tile = &t[next_pos];
+                let next_from_dir = next_dir.reverse();
+                pos_history.push(next_pos);
+                pos = next_pos;
+                tile = next_tile;
+                from_dir = next_from_dir;
+
+                if pos == start {
+                    assert!(matches!(tile, Tile::AN));
+                    if to_dir(*possible_tile, from_dir).is_some() {
+                        return Some((pos_history, *possible_tile));
+                    } else {
+                        break;
+                    }
+                }
             }
-            std::fmt::Write::write_char(f, '\n')?;
         }
-        Ok(())
     }
+    None
 }
 
-#[derive(Copy, Clone, Debug)]
-enum Dir {
-    N,
-    E,
-    S,
-    W,
+fn find_start(t: &Grid<Tile>) -> (usize, usize) {
+    t.find(|tile| matches!(tile, Tile::AN)).unwrap()
 }
 
-fn to_dir(t: Tile, from_dir: Dir) -> Option<Dir> {
-    match (t, from_dir) {
-        (Tile::NS, Dir::N) => Some(Dir::S),
-        (Tile::NS, Dir::E) => None,
-        (Tile::NS, Dir::S) => Some(Dir::N),
-        (Tile::NS, Dir::W) => None,
-        (Tile::EW, Dir::N) => None,
-        (Tile::EW, Dir::E) => Some(Dir::W),
-        (Tile::EW, Dir::S) => None,
-        (Tile::EW, Dir::W) => Some(Dir::E),
-        (Tile::NE, Dir::N) => Some(Dir::E),
-        (Tile::NE, Dir::E) => Some(Dir::N),
-        (Tile::NE, Dir::S) => None,
-        (Tile::NE, Dir::W) => None,
-        (Tile::NW, Dir::N) => Some(Dir::W),
-        (Tile::NW, Dir::E) => None,
-        (Tile::NW, Dir::S) => None,
-        (Tile::NW, Dir::W) => Some(Dir::N),
-        (Tile::SW, Dir::N) => None,
-        (Tile::SW, Dir::E) => None,
-        (Tile::SW, Dir::S) => Some(Dir::W),
-        (Tile::SW, Dir::W) => Some(Dir::S),
-        (Tile::SE, Dir::N) => None,
-        (Tile::SE, Dir::E) => Some(Dir::S),
-        (Tile::SE, Dir::S) => Some(Dir::E),
-        (Tile::SE, Dir::W) => None,
-        (Tile::GR, Dir::N) => None,
-        (Tile::GR, Dir::E) => None,
-        (Tile::GR, Dir::S) => None,
-        (Tile::GR, Dir::W) => None,
-        (Tile::AN, Dir::N) => None,
-        (Tile::AN, Dir::E) => None,
-        (Tile::AN, Dir::S) => None,
-        (Tile::AN, Dir::W) => None,
+/// The loop through the start, which `parse` has checked is there.
+fn start_loop(t: &Grid<Tile>) -> (Vec<(usize, usize)>, Tile) {
+    get_path(t, find_start(t)).unwrap()
+}
+
+#[derive(Debug)]
+enum Status {
+    IsLoop,
+    Untested,
+    Inside,
+    Outside,
+}
+
+/// Whether each tile is part of the loop, or inside or outside it.
+fn classify(t: &Grid<Tile>) -> Grid<Status> {
+    let (path, an_tile) = start_loop(t);
+
+    let mut s = t.map(|_| Status::Untested);
+
+    for pos in path.iter() {
+        s[*pos] = Status::IsLoop;
+    }
+
+    for pos in t.positions() {
+        if !matches!(s[pos], Status::Untested) {
+            continue;
+        }
+        // Go west until we're OOB or hit an outside tile
+        let mut in_score = 0isize;
+        let mut next_pos = pos;
+        loop {
+            let tile = t.offset(next_pos, (-1, 0));
+            if let Some(p) = tile {
+                next_pos = p;
+                let mut t = &t[p];
+                if matches!(t, Tile::AN) {
+                    t = &an_tile;
+                }
+                let st = &s[next_pos];
+                if matches!(st, Status::Outside) {
+                    break;
+                }
+                if matches!(st, Status::IsLoop) {
+                    in_score += match t {
+                        Tile::NS => 2,
+                        Tile::EW => 0,
+                        Tile::NE => 1,
+                        Tile::NW => -1,
+                        Tile::SW => 1,
+                        Tile::SE => -1,
+                        Tile::GR => 0,
+                        Tile::AN => panic!(),
+                    };
+                }
+            } else {
+                break;
+            }
+        }
+        if in_score % 4 == 0 {
+            s[pos] = Status::Outside;
+        } else {
+            s[pos] = Status::Inside;
+        }
+    }
+
+    s
+}
+
+/// The pipe joining the sides of a tile facing `a` and `b`.
+fn pipe(a: Dir, b: Dir) -> char {
+    match (a.min(b), a.max(b)) {
+        (Dir::Up, Dir::Down) => '|',
+        (Dir::Right, Dir::Left) => '-',
+        (Dir::Up, Dir::Right) => 'L',
+        (Dir::Up, Dir::Left) => 'J',
+        (Dir::Down, Dir::Left) => '7',
+        (Dir::Right, Dir::Down) => 'F',
+        _ => panic!("No pipe joins {a:?} to {b:?}"),
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut rows = Vec::<Vec<Tile>>::new();
-    for l in input.lines() {
-        let mut row = Vec::<Tile>::new();
-        for c in l.chars() {
-            let tile = match c {
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Grid<Tile>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+        let t = Grid::parse(input, "a pipe, `.` or `S`", |c| {
+            Some(match c {
                 '|' => Tile::NS,
                 '-' => Tile::EW,
                 'L' => Tile::NE,
@@ -97,152 +213,94 @@ pub fn main(input: &str) -> (String, String) {
                 'F' => Tile::SE,
                 '.' => Tile::GR,
                 'S' => Tile::AN,
-                other => panic!("{other}"),
-            };
-            row.push(tile);
+                _ => return None,
+            })
+        })?;
+        let start = t.find_only(input, "`S`", |tile| matches!(tile, Tile::AN))?;
+        if get_path(&t, start).is_none() {
+            return Err(grid::cell_error(input, start, "`S` on a loop of pipes"));
         }
-        rows.push(row);
-    }
-    let t = Tiles { rows };
-
-    fn get_path(t: &Tiles, start: (usize, usize)) -> (Vec<(usize, usize)>, Tile) {
-        assert!(matches!(t.rows[start.1][start.0], Tile::AN));
-        for possible_tile in [Tile::NS, Tile::EW, Tile::NE, Tile::NW, Tile::SW, Tile::SE].iter() {
-            for possible_dir in [Dir::N, Dir::E] {
-                let mut pos = start;
-                let mut tile = possible_tile;
-                let mut from_dir = possible_dir;
-                let mut pos_history = Vec::<(usize, usize)>::new();
-                loop {
-                    let next_dir = match to_dir(*tile, from_dir) {
-                        None => break,
-                        Some(d) => d,
-                    };
-                    let next_pos = match next_dir {
-                        Dir::N => (pos.0, pos.1.wrapping_sub(1)),
-                        Dir::E => (pos.0.wrapping_add(1), pos.1),
-                        Dir::S => (pos.0, pos.1.wrapping_add(1)),
-                        Dir::W => (pos.0.wrapping_sub(1), pos.1),
-                    };
-                    let next_tile = match t.rows.get(next_pos.1) {
-                        Some(r) => match r.get(next_pos.0) {
-                            Some(tile) => tile,
-                            None => break,
-                        },
-                        None => break,
-                    };
-                    let next_from_dir = match next_dir {
-                        Dir::N => Dir::S,
-                        Dir::E => Dir::W,
-                        Dir::S => Dir::N,
-                        Dir::W => Dir::E,
-                    };
-                    pos_history.push(next_pos);
-                    pos = next_pos;
-                    tile = next_tile;
-                    from_dir = next_from_dir;
-
-                    if pos == start {
-                        assert!(matches!(tile, Tile::AN));
-                        if to_dir(*possible_tile, from_dir).is_some() {
-                            return (pos_history, *possible_tile);
-                        } else {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-        panic!()
+        Ok(t)
     }
 
-    let an_start = || -> (usize, usize) {
-        for (y, row) in t.rows.iter().enumerate() {
-            for (x, tile) in row.iter().enumerate() {
-                if matches!(tile, Tile::AN) {
-                    return (x, y);
-                }
-            }
-        }
-        panic!();
-    }();
-
-    let (path, an_tile) = get_path(&t, an_start);
-    let num_steps = path.len() / 2;
-
-    #[derive(Debug)]
-    enum Status {
-        IsLoop,
-        Untested,
-        Inside,
-        Outside,
+    fn part1(t: &Grid<Tile>) -> usize {
+        let (path, _) = start_loop(t);
+        path.len() / 2
     }
 
-    let mut s = Vec::<Vec<Status>>::new();
-    for row in t.rows.iter() {
-        let mut v = Vec::<Status>::new();
-        v.resize_with(row.len(), || Status::Untested);
-        s.push(v);
+    fn part2(t: &Grid<Tile>) -> usize {
+        classify(t)
+            .cells()
+            .iter()
+            .filter(|st| matches!(st, Status::Inside))
+            .count()
     }
 
-    for pos in path.iter() {
-        s[pos.1][pos.0] = Status::IsLoop;
+    fn render(t: &Grid<Tile>, palette: &Palette) -> Option<Grid<Rgb>> {
+        Some(classify(t).map(|st| match st {
+            Status::IsLoop => palette.highlight,
+            Status::Inside => palette.inside,
+            Status::Outside => palette.outside,
+            Status::Untested => palette.background,
+        }))
     }
 
-    let rows = s.len();
-    let cols = s[0].len();
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let side = size.unwrap_or(140).max(7);
+        // Two runs of at most 3 steps per block keeps the loop inside the grid
+        let blocks = (side - 1) / 6;
+        let mut steps = Vec::<Dir>::new();
+        for (d, n) in generate::closed_loop(rng, blocks, blocks, 2..=3) {
+            steps.extend(std::iter::repeat_n(d, n));
+        }
+        let mut path = vec![Pos::default()];
+        for d in steps.iter() {
+            path.push(path.last().unwrap().step(*d, 1));
+        }
+        path.pop();
 
-    for y in 0..rows {
-        for x in 0..cols {
-            if !matches!(s[y][x], Status::Untested) {
-                continue;
-            }
-            // Go west until we're OOB or hit an outside tile
-            let mut in_score = 0isize;
-            let mut next_pos = (x, y);
-            loop {
-                next_pos = (next_pos.0.wrapping_sub(1), next_pos.1);
-                let tile = t.rows[next_pos.1].get(next_pos.0);
-                if let Some(mut t) = tile {
-                    if matches!(t, Tile::AN) {
-                        t = &an_tile;
-                    }
-                    let st = &s[next_pos.1][next_pos.0];
-                    if matches!(st, Status::Outside) {
-                        break;
-                    }
-                    if matches!(st, Status::IsLoop) {
-                        in_score += match t {
-                            Tile::NS => 2,
-                            Tile::EW => 0,
-                            Tile::NE => 1,
-                            Tile::NW => -1,
-                            Tile::SW => 1,
-                            Tile::SE => -1,
-                            Tile::GR => 0,
-                            Tile::AN => panic!(),
-                        };
-                    }
-                } else {
-                    break;
-                }
-            }
-            if in_score % 4 == 0 {
-                s[y][x] = Status::Outside;
-            } else {
-                s[y][x] = Status::Inside;
-            }
+        let min = Pos::new(
+            path.iter().map(|p| p.x).min().unwrap(),
+            path.iter().map(|p| p.y).min().unwrap(),
+        );
+        let max = Pos::new(
+            path.iter().map(|p| p.x).max().unwrap(),
+            path.iter().map(|p| p.y).max().unwrap(),
+        );
+        let offset = Pos::new(
+            rng.range(0..=side - 1 - (max.x - min.x) as usize) as isize - min.x,
+            rng.range(0..=side - 1 - (max.y - min.y) as usize) as isize - min.y,
+        );
+        let path = path
+            .iter()
+            .map(|p| {
+                Pos::new(p.x + offset.x, p.y + offset.y)
+                    .to_unsigned()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut g = Grid::filled(side, side, '.');
+        for c in g.cells_mut() {
+            *c = *rng.pick(b"|-LJ7F..") as char;
+        }
+        for (i, pos) in path.iter().enumerate() {
+            let came = steps[(i + steps.len() - 1) % steps.len()];
+            g[*pos] = pipe(came.reverse(), steps[i]);
         }
-    }
 
-    let mut inside_count = 0usize;
-    for (y, row) in t.rows.iter().enumerate() {
-        for (x, _) in row.iter().enumerate() {
-            if matches!(s[y][x], Status::Inside) {
-                inside_count += 1;
+        let start = rng.below(path.len());
+        g[path[start]] = 'S';
+        // Only the loop leads into the start, so there's no mistaking which way it goes
+        let joined = [
+            path[(start + 1) % path.len()],
+            path[(start + path.len() - 1) % path.len()],
+        ];
+        for pos in g.neighbours4(path[start]).collect::<Vec<_>>() {
+            if !joined.contains(&pos) {
+                g[pos] = '.';
             }
         }
+        g.to_string()
     }
-
-    (format!("{num_steps}"), format!("{inside_count}"))
 }
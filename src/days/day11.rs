@@ -1,98 +1,99 @@
+use super::Solution;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+
 #[derive(Clone, Debug)]
-enum Point {
+pub enum Point {
     Empty,
     Galaxy,
 }
 
-#[derive(Clone, Debug)]
-struct ImageRow {
-    row: Vec<Point>,
-}
+fn calc_shortest_dist_sum(undilated: &Grid<Point>, expansion_factor: usize) -> usize {
+    let is_row_dilated = undilated
+        .rows()
+        .map(|r| r.iter().all(|p| matches!(p, Point::Empty)))
+        .collect::<Vec<_>>();
+    let is_col_dilated = undilated
+        .columns()
+        .map(|mut c| c.all(|p| matches!(p, Point::Empty)))
+        .collect::<Vec<_>>();
+
+    let gal_coord = undilated
+        .iter()
+        .filter(|(_, p)| matches!(p, Point::Galaxy))
+        .map(|(pos, _)| pos)
+        .collect::<Vec<_>>();
+
+    let mut shortest_dist_sum = 0usize;
+    for i in 0..gal_coord.len() {
+        for j in i + 1..gal_coord.len() {
+            let this = gal_coord[i];
+            let that = gal_coord[j];
+
+            let mut shortest_dist = 0usize;
+
+            let mut xr = [this.0, that.0];
+            xr.sort();
+
+            for is_dilated in is_col_dilated[xr[0]..xr[1]].iter() {
+                match is_dilated {
+                    true => shortest_dist += expansion_factor,
+                    false => shortest_dist += 1,
+                }
+            }
 
-#[derive(Debug)]
-struct Image {
-    rows: Vec<ImageRow>,
-}
+            let mut yr = [this.1, that.1];
+            yr.sort();
 
-pub fn main(input: &str) -> (String, String) {
-    let mut rows = Vec::<ImageRow>::new();
-    for l in input.lines() {
-        let mut row = Vec::<Point>::new();
-        for c in l.chars() {
-            let p = match c {
-                '.' => Point::Empty,
-                '#' => Point::Galaxy,
-                other => panic!("{other}"),
-            };
-            row.push(p);
+            for is_dilated in is_row_dilated[yr[0]..yr[1]].iter() {
+                match is_dilated {
+                    true => shortest_dist += expansion_factor,
+                    false => shortest_dist += 1,
+                }
+            }
+
+            shortest_dist_sum += shortest_dist;
         }
-        rows.push(ImageRow { row });
     }
-    let undilated = Image { rows };
+    shortest_dist_sum
+}
 
-    let mut is_row_dilated = Vec::<bool>::new();
-    let mut is_col_dilated = Vec::<bool>::new();
+pub struct Day11;
 
-    for row in undilated.rows.iter() {
-        is_row_dilated.push(row.row.iter().all(|p| matches!(p, Point::Empty)));
-    }
+impl Solution for Day11 {
+    type Input = Grid<Point>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    for x in 0..undilated.rows[0].row.len() {
-        is_col_dilated.push(
-            undilated
-                .rows
-                .iter()
-                .all(|r| matches!(r.row[x], Point::Empty)),
-        );
+    fn parse(input: &str) -> Result<Grid<Point>, ParseError> {
+        Grid::parse(input, "`.` or `#`", |c| match c {
+            '.' => Some(Point::Empty),
+            '#' => Some(Point::Galaxy),
+            _ => None,
+        })
     }
 
-    let mut gal_coord = Vec::<(usize, usize)>::new();
-
-    for (y, r) in undilated.rows.iter().enumerate() {
-        for (x, p) in r.row.iter().enumerate() {
-            if matches!(p, Point::Galaxy) {
-                gal_coord.push((x, y));
-            }
-        }
+    fn part1(image: &Grid<Point>) -> usize {
+        calc_shortest_dist_sum(image, 2)
     }
 
-    let calc_shortest_dist_sum = |expansion_factor: usize| -> usize {
-        let mut shortest_dist_sum = 0usize;
-        for i in 0..gal_coord.len() {
-            for j in i + 1..gal_coord.len() {
-                let this = gal_coord[i];
-                let that = gal_coord[j];
-
-                let mut shortest_dist = 0usize;
-
-                let mut xr = [this.0, that.0];
-                xr.sort();
-
-                for x in xr[0]..xr[1] {
-                    match is_col_dilated[x] {
-                        true => shortest_dist += expansion_factor,
-                        false => shortest_dist += 1,
-                    }
-                }
-
-                let mut yr = [this.1, that.1];
-                yr.sort();
-
-                for y in yr[0]..yr[1] {
-                    match is_row_dilated[y] {
-                        true => shortest_dist += expansion_factor,
-                        false => shortest_dist += 1,
-                    }
-                }
+    fn part2(image: &Grid<Point>) -> usize {
+        calc_shortest_dist_sum(image, 1000000)
+    }
 
-                shortest_dist_sum += shortest_dist;
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let side = size.unwrap_or(140);
+        let empty_rows = (0..side).map(|_| rng.chance(0.06)).collect::<Vec<_>>();
+        let empty_cols = (0..side).map(|_| rng.chance(0.06)).collect::<Vec<_>>();
+        let mut out = String::new();
+        for empty_row in empty_rows.iter() {
+            for empty_col in empty_cols.iter() {
+                let galaxy = !empty_row && !empty_col && rng.chance(0.025);
+                out.push(if galaxy { '#' } else { '.' });
             }
+            out.push('\n');
         }
-        shortest_dist_sum
-    };
-
-    let expansion_2_sum = calc_shortest_dist_sum(2);
-    let expansion_mil_sum = calc_shortest_dist_sum(1000000);
-
-    (format!("{expansion_2_sum}"), format!("{expansion_mil_sum}"))
+        out
+    }
 }
@@ -1,16 +1,22 @@
 use std::{collections::HashMap, fmt::Display};
 
+use rayon::prelude::*;
+
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+
 #[derive(Debug, Copy, Clone)]
-enum State {
+pub enum State {
     Operational,
     Damaged,
     Unknown,
 }
 
 #[derive(Debug)]
-struct Record {
-    states: Vec<State>,
-    groups: Vec<usize>,
+pub struct Record {
+    pub states: Vec<State>,
+    pub groups: Vec<usize>,
 }
 
 impl Display for Record {
@@ -28,118 +34,158 @@ impl Display for Record {
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut records = Vec::<Record>::new();
-    for l in input.lines() {
-        let mut n = l.split_whitespace();
-        let ss = n.next().unwrap();
-        let cr = n.next().unwrap();
-        assert!(n.next().is_none());
-        let states = ss
-            .chars()
-            .map(|c| match c {
-                '#' => State::Damaged,
-                '.' => State::Operational,
-                '?' => State::Unknown,
-                other => panic!("{other}"),
-            })
-            .collect::<Vec<_>>();
-        let groups = cr
-            .split(",")
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
-        records.push(Record { states, groups });
+fn options_count_folded(r: &Record, c: usize) -> usize {
+    let mut states = r.states.clone();
+    let mut groups = r.groups.clone();
+    for _ in 0..c {
+        states.push(State::Unknown);
+        states.extend(&r.states);
+        groups.extend(&r.groups);
     }
 
-    let options_count_folded = |r: &Record, c: usize| -> usize {
-        let mut states = r.states.clone();
-        let mut groups = r.groups.clone();
-        for _ in 0..c {
-            states.push(State::Unknown);
-            states.extend(&r.states);
-            groups.extend(&r.groups);
-        }
-
-        let states = states;
-        let groups = groups;
-
-        fn calc(
-            states: &Vec<State>,
-            groups: &Vec<usize>,
-            state_idx: usize,
-            group_idx: usize,
-            memo: &mut HashMap<(usize, usize), usize>,
-        ) -> usize {
-            let cur_group = groups[group_idx];
-
-            let mut count = 0usize;
-            for i in state_idx..states.len() - (cur_group - 1) {
-                // If the previous state is damaged, it's not possible to fit this group in here.
-                // We've gone too far.
-                if let Some(State::Damaged) = states.get(i.wrapping_sub(1)) {
-                    break;
-                }
+    let states = states;
+    let groups = groups;
+
+    fn calc(
+        states: &[State],
+        groups: &[usize],
+        state_idx: usize,
+        group_idx: usize,
+        memo: &mut HashMap<(usize, usize), usize>,
+    ) -> usize {
+        let cur_group = groups[group_idx];
+
+        let mut count = 0usize;
+        for i in state_idx..states.len() - (cur_group - 1) {
+            // If the previous state is damaged, it's not possible to fit this group in here.
+            // We've gone too far.
+            if let Some(State::Damaged) = states.get(i.wrapping_sub(1)) {
+                break;
+            }
 
-                // Can all the required states for the group be made damaged here?
-                if !states[i..i + cur_group]
-                    .iter()
-                    .all(|s| !matches!(s, State::Operational))
-                {
-                    continue;
-                }
+            // Can all the required states for the group be made damaged here?
+            if !states[i..i + cur_group]
+                .iter()
+                .all(|s| !matches!(s, State::Operational))
+            {
+                continue;
+            }
 
-                // Is the next state not damaged?
-                if let Some(State::Damaged) = states.get(i + cur_group) {
-                    continue;
-                }
+            // Is the next state not damaged?
+            if let Some(State::Damaged) = states.get(i + cur_group) {
+                continue;
+            }
 
-                let next_group = group_idx + 1;
-                let next_state = i + cur_group + 1;
-
-                if next_group < groups.len() {
-                    // If there are more groups, either get the memoized result or calculate a new result.
-                    match memo.get(&(next_state, next_group)) {
-                        Some(v) => count += *v,
-                        None => {
-                            let v = calc(states, groups, next_state, next_group, memo);
-                            memo.insert((next_state, next_group), v);
-                            count += v;
-                        }
+            let next_group = group_idx + 1;
+            let next_state = i + cur_group + 1;
+
+            if next_group < groups.len() {
+                // If there are more groups, either get the memoized result or calculate a new result.
+                match memo.get(&(next_state, next_group)) {
+                    Some(v) => count += *v,
+                    None => {
+                        let v = calc(states, groups, next_state, next_group, memo);
+                        memo.insert((next_state, next_group), v);
+                        count += v;
                     }
+                }
+            } else {
+                // Make sure there aren't any more future damaged states.
+                let extra_damaged = if next_state >= states.len() {
+                    0
                 } else {
-                    // Make sure there aren't any more future damaged states.
-                    let extra_damaged = if next_state >= states.len() {
-                        0
-                    } else {
-                        states[next_state..]
-                            .iter()
-                            .filter(|s| matches!(s, State::Damaged))
-                            .count()
-                    };
-                    if extra_damaged == 0 {
-                        count += 1;
-                    }
+                    states[next_state..]
+                        .iter()
+                        .filter(|s| matches!(s, State::Damaged))
+                        .count()
+                };
+                if extra_damaged == 0 {
+                    count += 1;
                 }
             }
-            count
         }
-
-        let mut memo = HashMap::<(usize, usize), usize>::new();
-        let count = calc(&states, &groups, 0, 0, &mut memo);
         count
-    };
+    }
 
-    let folded = records
-        .iter()
-        .map(|r| options_count_folded(r, 0))
-        .collect::<Vec<_>>();
-    let folded_sum = folded.iter().sum::<usize>();
+    let mut memo = HashMap::<(usize, usize), usize>::new();
+    calc(&states, &groups, 0, 0, &mut memo)
+}
 
-    let unfolded_4 = records
-        .iter()
-        .map(|r| options_count_folded(r, 4))
-        .collect::<Vec<_>>();
-    let unfolded_4_sum = unfolded_4.iter().sum::<usize>();
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<Record>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Record>, ParseError> {
+        let mut records = Vec::<Record>::new();
+        for line in parse::lines(input) {
+            let mut n = line.text.split_whitespace();
+            let ss = n.next().ok_or_else(|| line.end_error("spring states"))?;
+            let cr = n.next().ok_or_else(|| line.end_error("group sizes"))?;
+            if let Some(extra) = n.next() {
+                return Err(line.error_at(extra, "end of line"));
+            }
+            let states = ss
+                .chars()
+                .enumerate()
+                .map(|(i, c)| match c {
+                    '#' => Ok(State::Damaged),
+                    '.' => Ok(State::Operational),
+                    '?' => Ok(State::Unknown),
+                    other => Err(line.char_error(i, other, "`#`, `.` or `?`")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let expected = format!("a group size from 1 to {}", states.len());
+            let groups = cr
+                .split(',')
+                .map(|g| match line.parse::<usize>(g, &expected)? {
+                    0 => Err(line.error_at(g, &expected)),
+                    n if n > states.len() => Err(line.error_at(g, &expected)),
+                    n => Ok(n),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            records.push(Record { states, groups });
+        }
 
-    (format!("{folded_sum}"), format!("{unfolded_4_sum}"))
+        Ok(records)
+    }
+
+    fn part1(records: &Vec<Record>) -> usize {
+        records.par_iter().map(|r| options_count_folded(r, 0)).sum()
+    }
+
+    fn part2(records: &Vec<Record>) -> usize {
+        records.par_iter().map(|r| options_count_folded(r, 4)).sum()
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let mut out = String::new();
+        for _ in 0..size.unwrap_or(1000) {
+            let len = rng.range(8..=20);
+            let (damaged, groups) = loop {
+                let damaged = (0..len).map(|_| rng.chance(0.5)).collect::<Vec<_>>();
+                let groups = damaged
+                    .split(|d| !d)
+                    .map(|g| g.len())
+                    .filter(|l| *l > 0)
+                    .collect::<Vec<_>>();
+                if !groups.is_empty() {
+                    break (damaged, groups);
+                }
+            };
+            let states = damaged
+                .iter()
+                .map(|d| match (rng.chance(0.5), d) {
+                    (true, _) => '?',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                })
+                .collect::<String>();
+            let groups = groups.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+            out += &format!("{states} {}\n", groups.join(","));
+        }
+        out
+    }
 }
@@ -1,126 +1,217 @@
-use std::iter::once;
+use rayon::prelude::*;
+
+use super::Solution;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::{self, Line, ParseError};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
-enum Ground {
+pub enum Ground {
     Ash,
     Rock,
 }
 
 #[derive(Clone, Debug)]
-struct Pattern {
-    rows: Vec<Vec<Ground>>,
-    cols: Vec<Vec<Ground>>,
+pub struct Pattern {
+    pub rows: Grid<Ground>,
+    pub cols: Grid<Ground>,
 }
 
 impl Pattern {
-    fn new(rows: Vec<Vec<Ground>>) -> Pattern {
-        let cols = (0..rows[0].len())
-            .map(|c| rows.iter().map(|r| r[c]).collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+    pub fn new(rows: Grid<Ground>) -> Pattern {
+        let cols = rows.transpose();
         Pattern { rows, cols }
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut patterns = Vec::<Pattern>::new();
-
-    let mut rows = Vec::<Vec<Ground>>::new();
-    for l in input.lines().chain(once("")) {
-        if l.is_empty() {
-            if !rows.is_empty() {
-                patterns.push(Pattern::new(rows.clone()));
-                rows.clear();
-            }
-        } else {
-            rows.push(
-                l.chars()
-                    .map(|c| match c {
-                        '#' => Ground::Rock,
-                        '.' => Ground::Ash,
-                        other => panic!("{other}"),
-                    })
-                    .collect::<Vec<_>>(),
-            );
-        }
+fn flip(g: Ground) -> Ground {
+    match g {
+        Ground::Ash => Ground::Rock,
+        Ground::Rock => Ground::Ash,
     }
-    assert!(rows.is_empty());
+}
 
-    fn pre_refl_idx(gs: &Vec<Vec<Ground>>, ignore: Option<usize>) -> Option<usize> {
-        for r in 0..(gs.len() - 1) {
-            if ignore.is_some_and(|ignore| ignore == r) {
-                continue;
-            }
-            let mut off = 0usize;
-            loop {
-                let pre_ri = r.wrapping_sub(off);
-                let post_ri = r + 1 + off;
-                let pre_r = gs.get(pre_ri);
-                let post_r = gs.get(post_ri);
-
-                match (pre_r, post_r) {
-                    (Some(pre), Some(post)) => {
-                        if pre != post {
-                            break;
-                        }
+fn pre_refl_idx(gs: &Grid<Ground>, ignore: Option<usize>) -> Option<usize> {
+    for r in 0..(gs.height() - 1) {
+        if ignore.is_some_and(|ignore| ignore == r) {
+            continue;
+        }
+        let mut off = 0usize;
+        loop {
+            let pre_ri = r.checked_sub(off);
+            let post_ri = r + 1 + off;
+
+            match pre_ri {
+                Some(pre) if post_ri < gs.height() => {
+                    if gs.row(pre) != gs.row(post_ri) {
+                        break;
                     }
-                    (_, _) => return Some(r),
                 }
-
-                off += 1;
+                _ => return Some(r),
             }
+
+            off += 1;
         }
+    }
+
+    None
+}
+
+fn score(p: &Pattern) -> usize {
+    let row_pre_refl_idx = pre_refl_idx(&p.rows, None);
+    let col_pre_refl_idx = pre_refl_idx(&p.cols, None);
 
-        None
+    match (row_pre_refl_idx, col_pre_refl_idx) {
+        (None, Some(c)) => c + 1,
+        (Some(r), None) => (r + 1) * 100,
+        _ => unreachable!("parse checked for one reflection"),
     }
+}
+
+fn score2(p: &Pattern) -> usize {
+    let og_row_pre_refl_idx = pre_refl_idx(&p.rows, None);
+    let og_col_pre_refl_idx = pre_refl_idx(&p.cols, None);
 
-    fn score(p: &Pattern) -> usize {
-        let row_pre_refl_idx = pre_refl_idx(&p.rows, None);
-        let col_pre_refl_idx = pre_refl_idx(&p.cols, None);
+    for pos in p.rows.positions() {
+        let mut rows = p.rows.clone();
+        rows[pos] = flip(rows[pos]);
+        let p2 = Pattern::new(rows);
+
+        let row_pre_refl_idx = pre_refl_idx(&p2.rows, og_row_pre_refl_idx);
+        let col_pre_refl_idx = pre_refl_idx(&p2.cols, og_col_pre_refl_idx);
 
         match (row_pre_refl_idx, col_pre_refl_idx) {
-            (None, None) => panic!("No matches"),
-            (None, Some(c)) => c + 1,
-            (Some(r), None) => (r + 1) * 100,
-            (Some(_), Some(_)) => panic!("Both matches"),
+            (None, None) => (),
+            (None, Some(c)) => {
+                return c + 1;
+            }
+            (Some(r), None) => {
+                return (r + 1) * 100;
+            }
+            (Some(_), Some(_)) => unreachable!("parse checked for one reflection"),
         }
     }
+    unreachable!("parse checked for a smudge")
+}
 
-    let scores: Vec<usize> = patterns.iter().map(|p| score(p)).collect::<Vec<_>>();
-    let summary = scores.iter().sum::<usize>();
-
-    fn score2(p: &Pattern) -> usize {
-        let og_row_pre_refl_idx = pre_refl_idx(&p.rows, None);
-        let og_col_pre_refl_idx = pre_refl_idx(&p.cols, None);
-
-        for row in 0..p.rows.len() {
-            for col in 0..p.rows[0].len() {
-                let mut rows = p.rows.clone();
-                rows[row][col] = match rows[row][col] {
-                    Ground::Ash => Ground::Rock,
-                    Ground::Rock => Ground::Ash,
-                };
-                let p2 = Pattern::new(rows);
-
-                let row_pre_refl_idx = pre_refl_idx(&p2.rows, og_row_pre_refl_idx);
-                let col_pre_refl_idx = pre_refl_idx(&p2.cols, og_col_pre_refl_idx);
-
-                match (row_pre_refl_idx, col_pre_refl_idx) {
-                    (None, None) => (),
-                    (None, Some(c)) => {
-                        return c + 1;
-                    }
-                    (Some(r), None) => {
-                        return (r + 1) * 100;
-                    }
-                    (Some(_), Some(_)) => panic!("Both matches!"),
-                }
+fn parse_pattern(lines: Vec<Line>) -> Result<Pattern, ParseError> {
+    let first = lines[0];
+    let rows = Grid::parse_lines(lines, "`#` or `.`", |c| match c {
+        '#' => Some(Ground::Rock),
+        '.' => Some(Ground::Ash),
+        _ => None,
+    })?;
+    let p = Pattern::new(rows);
+    if !has_answers(&p) {
+        return Err(first.error_at(
+            first.text,
+            "a pattern with one line of reflection, and one other with a smudge cleaned",
+        ));
+    }
+    Ok(p)
+}
+
+/// Whether `p` has the one reflection part 1 looks for, and a smudge that gives it another.
+fn has_answers(p: &Pattern) -> bool {
+    let row = pre_refl_idx(&p.rows, None);
+    let col = pre_refl_idx(&p.cols, None);
+    if row.is_some() == col.is_some() {
+        return false;
+    }
+    for pos in p.rows.positions() {
+        let mut rows = p.rows.clone();
+        rows[pos] = flip(rows[pos]);
+        let p2 = Pattern::new(rows);
+        match (pre_refl_idx(&p2.rows, row), pre_refl_idx(&p2.cols, col)) {
+            (None, None) => (),
+            (r, c) => return r.is_none() || c.is_none(),
+        }
+    }
+    false
+}
+
+/// A pattern reflected about a row, off centre so some rows are left out of it, and about a
+/// column except for a smudge in one of the rows left out.
+fn generate_pattern(rng: &mut Rng) -> Pattern {
+    loop {
+        let (width, height) = (rng.range(5..=17), rng.range(5..=17));
+        let cells = (0..width * height)
+            .map(|_| *rng.pick(&[Ground::Ash, Ground::Rock]))
+            .collect();
+        let mut rows = Grid::new(width, height, cells);
+
+        let col = rng.below(width - 1);
+        let col_reach = col.min(width - col - 2);
+        for off in 0..=col_reach {
+            for y in 0..height {
+                rows[(col + 1 + off, y)] = rows[(col - off, y)];
             }
         }
-        panic!("No matches!");
+
+        let row = loop {
+            let row = rng.below(height - 1);
+            if row + 1 != height - row - 1 {
+                break row;
+            }
+        };
+        let row_reach = row.min(height - row - 2);
+        for off in 0..=row_reach {
+            for x in 0..width {
+                rows[(x, row + 1 + off)] = rows[(x, row - off)];
+            }
+        }
+
+        let left_out = (0..height)
+            .filter(|y| !(row - row_reach..=row + 1 + row_reach).contains(y))
+            .collect::<Vec<_>>();
+        let y = *rng.pick(&left_out);
+        let x = rng.range(col - col_reach..=col + 1 + col_reach);
+        rows[(x, y)] = flip(rows[(x, y)]);
+
+        let mut p = Pattern::new(rows);
+        if rng.chance(0.5) {
+            p = Pattern::new(p.cols);
+        }
+        if has_answers(&p) {
+            return p;
+        }
     }
+}
+
+pub struct Day13;
 
-    let scores2: Vec<usize> = patterns.iter().map(|p| score2(p)).collect::<Vec<_>>();
-    let summary2 = scores2.iter().sum::<usize>();
+impl Solution for Day13 {
+    type Input = Vec<Pattern>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    (format!("{summary}"), format!("{summary2}"))
+    fn parse(input: &str) -> Result<Vec<Pattern>, ParseError> {
+        parse::sections(input)
+            .into_iter()
+            .map(parse_pattern)
+            .collect()
+    }
+
+    fn part1(patterns: &Vec<Pattern>) -> usize {
+        patterns.par_iter().map(score).sum()
+    }
+
+    fn part2(patterns: &Vec<Pattern>) -> usize {
+        patterns.par_iter().map(score2).sum()
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let patterns = (0..size.unwrap_or(100))
+            .map(|_| {
+                let p = generate_pattern(rng);
+                p.rows
+                    .map(|g| match g {
+                        Ground::Ash => '.',
+                        Ground::Rock => '#',
+                    })
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        patterns.join("\n")
+    }
 }
@@ -1,169 +1,187 @@
 use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::Solution;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::log;
+use crate::parse::ParseError;
+use crate::render::{Palette, Rgb};
+use crate::trace::{self, Frame};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
-enum Ground {
+pub enum Ground {
     Round,
     Empty,
     Cube,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut rows = Vec::<Vec<Ground>>::new();
-    for l in input.lines() {
-        rows.push(
-            l.chars()
-                .map(|c| match c {
-                    'O' => Ground::Round,
-                    '.' => Ground::Empty,
-                    '#' => Ground::Cube,
-                    other => panic!("{other}"),
-                })
-                .collect::<Vec<_>>(),
-        );
+impl Display for Ground {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Ground::Round => 'O',
+            Ground::Empty => '.',
+            Ground::Cube => '#',
+        };
+        write!(f, "{c}")
     }
-    let rows = rows;
-
-    #[allow(dead_code)]
-    fn pr(rows: &Vec<Vec<Ground>>) {
-        for row in rows {
-            for g in row {
-                let c = match g {
-                    Ground::Round => 'O',
-                    Ground::Empty => '.',
-                    Ground::Cube => '#',
-                };
-                print!("{c}");
-            }
-            println!();
-        }
-        println!();
+}
+
+/// Rolls the rock at `src`, if there is one, into `dst` if that's empty.
+fn step(rows: &mut Grid<Ground>, src: (usize, usize), dst: (usize, usize)) -> bool {
+    if matches!(rows[dst], Ground::Empty) && matches!(rows[src], Ground::Round) {
+        rows[dst] = Ground::Round;
+        rows[src] = Ground::Empty;
+        true
+    } else {
+        false
     }
+}
 
-    fn north(rows: &mut Vec<Vec<Ground>>) -> bool {
-        let mut any_moved = false;
-        for r_idx in 1..rows.len() {
-            for c_idx in 0..rows[0].len() {
-                let dst = &rows[r_idx - 1][c_idx];
-                let src = &rows[r_idx][c_idx];
-
-                if matches!(dst, Ground::Empty) && matches!(src, Ground::Round) {
-                    rows[r_idx - 1][c_idx] = Ground::Round;
-                    rows[r_idx][c_idx] = Ground::Empty;
-                    any_moved = true;
-                }
-            }
+fn north(rows: &mut Grid<Ground>) -> bool {
+    let mut any_moved = false;
+    for y in 1..rows.height() {
+        for x in 0..rows.width() {
+            any_moved |= step(rows, (x, y), (x, y - 1));
         }
-        any_moved
     }
+    any_moved
+}
 
-    fn east(rows: &mut Vec<Vec<Ground>>) -> bool {
-        let mut any_moved = false;
-        for c_idx in 0..(rows.len() - 1) {
-            for r_idx in 0..rows.len() {
-                let dst = &rows[r_idx][c_idx + 1];
-                let src = &rows[r_idx][c_idx];
-
-                if matches!(dst, Ground::Empty) && matches!(src, Ground::Round) {
-                    rows[r_idx][c_idx + 1] = Ground::Round;
-                    rows[r_idx][c_idx] = Ground::Empty;
-                    any_moved = true;
-                }
-            }
+fn east(rows: &mut Grid<Ground>) -> bool {
+    let mut any_moved = false;
+    for x in 0..(rows.width() - 1) {
+        for y in 0..rows.height() {
+            any_moved |= step(rows, (x, y), (x + 1, y));
         }
-        any_moved
     }
+    any_moved
+}
 
-    fn south(rows: &mut Vec<Vec<Ground>>) -> bool {
-        let mut any_moved = false;
-        for r_idx in (0..(rows.len() - 1)).rev() {
-            for c_idx in 0..rows[0].len() {
-                let dst = &rows[r_idx + 1][c_idx];
-                let src = &rows[r_idx][c_idx];
-
-                if matches!(dst, Ground::Empty) && matches!(src, Ground::Round) {
-                    rows[r_idx + 1][c_idx] = Ground::Round;
-                    rows[r_idx][c_idx] = Ground::Empty;
-                    any_moved = true;
-                }
-            }
+fn south(rows: &mut Grid<Ground>) -> bool {
+    let mut any_moved = false;
+    for y in (0..(rows.height() - 1)).rev() {
+        for x in 0..rows.width() {
+            any_moved |= step(rows, (x, y), (x, y + 1));
         }
-        any_moved
     }
+    any_moved
+}
 
-    fn west(rows: &mut Vec<Vec<Ground>>) -> bool {
-        let mut any_moved = false;
-        for c_idx in (1..rows[0].len()).rev() {
-            for r_idx in 0..rows.len() {
-                let dst = &rows[r_idx][c_idx - 1];
-                let src = &rows[r_idx][c_idx];
-
-                if matches!(dst, Ground::Empty) && matches!(src, Ground::Round) {
-                    rows[r_idx][c_idx - 1] = Ground::Round;
-                    rows[r_idx][c_idx] = Ground::Empty;
-                    any_moved = true;
-                }
-            }
+fn west(rows: &mut Grid<Ground>) -> bool {
+    let mut any_moved = false;
+    for x in (1..rows.width()).rev() {
+        for y in 0..rows.height() {
+            any_moved |= step(rows, (x, y), (x - 1, y));
         }
-        any_moved
     }
+    any_moved
+}
 
-    fn roll<F>(op: F, rows: &mut Vec<Vec<Ground>>)
-    where
-        F: Fn(&mut Vec<Vec<Ground>>) -> bool,
-    {
-        loop {
-            let any_moved = op(rows);
-            if !any_moved {
-                break;
-            }
+fn colour(g: &Ground, palette: &Palette) -> Rgb {
+    match g {
+        Ground::Round => palette.highlight,
+        Ground::Cube => palette.feature,
+        Ground::Empty => palette.background,
+    }
+}
+
+fn roll<F>(op: F, name: &str, rows: &mut Grid<Ground>)
+where
+    F: Fn(&mut Grid<Ground>) -> bool,
+{
+    loop {
+        let any_moved = op(rows);
+        if !any_moved {
+            break;
         }
+        trace::record(|palette| Frame::of_grid(name, rows, |g| colour(g, palette)));
     }
+}
+
+fn total_load(rows: &Grid<Ground>) -> usize {
+    rows.rows()
+        .enumerate()
+        .map(|(i, r)| {
+            let round_count = r.iter().filter(|g| matches!(g, Ground::Round)).count();
+            round_count * (rows.height() - i)
+        })
+        .sum()
+}
 
-    fn total_load(rows: &Vec<Vec<Ground>>) -> usize {
-        let round_count_per_row = rows
-            .iter()
-            .map(|r| r.iter().filter(|g| matches!(g, Ground::Round)).count())
-            .collect::<Vec<_>>();
-
-        let load_per_row = round_count_per_row
-            .iter()
-            .enumerate()
-            .map(|(i, c)| c * (rows.len() - i))
-            .collect::<Vec<_>>();
-        load_per_row.iter().sum::<usize>()
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Grid<Ground>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Grid<Ground>, ParseError> {
+        Grid::parse(input, "`O`, `.` or `#`", |c| match c {
+            'O' => Some(Ground::Round),
+            '.' => Some(Ground::Empty),
+            '#' => Some(Ground::Cube),
+            _ => None,
+        })?
+        .non_empty(input)
     }
 
-    let mut north_rows = rows.clone();
-    roll(north, &mut north_rows);
-    let north_total_load = total_load(&north_rows);
+    fn part1(rows: &Grid<Ground>) -> usize {
+        let mut north_rows = rows.clone();
+        roll(north, "north", &mut north_rows);
+        total_load(&north_rows)
+    }
 
-    let mut cycle_rows = rows.clone();
+    fn part2(rows: &Grid<Ground>) -> usize {
+        let mut cycle_rows = rows.clone();
 
-    let mut row_to_idx = HashMap::<Vec<Vec<Ground>>, usize>::new();
-    row_to_idx.insert(rows.clone(), 0);
+        let mut row_to_idx = HashMap::<Grid<Ground>, usize>::new();
+        row_to_idx.insert(rows.clone(), 0);
 
-    const ITS: usize = 1000000000;
-    let mut i = 0;
-    loop {
-        roll(north, &mut cycle_rows);
-        roll(west, &mut cycle_rows);
-        roll(south, &mut cycle_rows);
-        roll(east, &mut cycle_rows);
-
-        i += 1;
-        if let Some(v) = row_to_idx.get(&cycle_rows) {
-            let dist = i - v;
-            let remaining = ITS - i;
-            i += dist * (remaining / dist);
-        } else {
-            row_to_idx.insert(cycle_rows.clone(), i);
-        }
-        if i == ITS {
-            break;
+        const ITS: usize = 1000000000;
+        let mut i = 0;
+        loop {
+            roll(north, "north", &mut cycle_rows);
+            roll(west, "west", &mut cycle_rows);
+            roll(south, "south", &mut cycle_rows);
+            roll(east, "east", &mut cycle_rows);
+
+            i += 1;
+            log::trace(|| format!("Cycle {i} leaves a load of {}", total_load(&cycle_rows)));
+            if let Some(v) = row_to_idx.get(&cycle_rows) {
+                let dist = i - v;
+                let remaining = ITS - i;
+                if remaining >= dist {
+                    log::info(|| format!("Cycle {i} repeats cycle {v}, skipping ahead"));
+                }
+                i += dist * (remaining / dist);
+            } else {
+                row_to_idx.insert(cycle_rows.clone(), i);
+            }
+            if i == ITS {
+                break;
+            }
         }
+
+        total_load(&cycle_rows)
     }
 
-    let cycle_total_load = total_load(&cycle_rows);
+    /// The platform after rolling everything north.
+    fn render(rows: &Grid<Ground>, palette: &Palette) -> Option<Grid<Rgb>> {
+        let mut north_rows = rows.clone();
+        roll(north, "north", &mut north_rows);
+        Some(north_rows.map(|g| colour(g, palette)))
+    }
 
-    (format!("{north_total_load}"), format!("{cycle_total_load}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let side = size.unwrap_or(100);
+        let cells = (0..side * side)
+            .map(|_| match rng.below(20) {
+                0..=3 => 'O',
+                4..=6 => '#',
+                _ => '.',
+            })
+            .collect();
+        Grid::new(side, side, cells).to_string()
+    }
 }
@@ -1,67 +1,125 @@
-pub fn main(input: &str) -> (String, String) {
-    let mut lines = input.lines();
-    let l = lines.next();
+use super::Solution;
+use crate::generate::{Rng, LOWERCASE};
+use crate::parse::{self, ParseError};
 
-    fn hash_str(s: &str) -> usize {
-        let mut cur = 0usize;
-        for c in s.chars() {
-            assert!(c.is_ascii());
-            let v = c as usize;
-            cur += v;
-            cur *= 17;
-            cur %= 256;
-        }
-        cur
+fn hash_str(s: &str) -> usize {
+    let mut cur = 0usize;
+    for c in s.chars() {
+        assert!(c.is_ascii());
+        let v = c as usize;
+        cur += v;
+        cur *= 17;
+        cur %= 256;
     }
+    cur
+}
 
-    let strings = l
-        .unwrap()
-        .split(",")
-        .map(|s| s.to_owned())
-        .collect::<Vec<_>>();
-    let hashed_strs = strings.iter().map(|s| hash_str(s)).collect::<Vec<_>>();
-    assert!(lines.next().is_none());
+pub enum Op {
+    Remove,
+    Insert(usize),
+}
 
-    let hashed_sum = hashed_strs.iter().sum::<usize>();
+pub struct Step {
+    /// The step as written, which is what part 1 hashes.
+    pub text: String,
+    pub label: String,
+    pub op: Op,
+}
 
-    let mut boxes = Vec::<Vec<(String, usize)>>::new();
-    boxes.resize_with(256, || Vec::<(String, usize)>::new());
+pub struct Day15;
 
-    for s in strings.iter() {
-        if s.contains("=") {
-            let mut ss = s.split("=");
-            let label = ss.next().unwrap();
-            let hash = hash_str(label);
-            let fl = ss.next().unwrap().parse::<usize>().unwrap();
+impl Solution for Day15 {
+    type Input = Vec<Step>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-            let b = &mut boxes[hash];
-            let mut replaced = false;
-            for l in b.iter_mut() {
-                if l.0 == label {
-                    l.1 = fl;
-                    replaced = true;
-                    break;
-                }
-            }
-            if !replaced {
-                b.push((label.to_owned(), fl));
+    fn parse(input: &str) -> Result<Vec<Step>, ParseError> {
+        let mut lines = parse::lines(input);
+        let l = lines
+            .next()
+            .ok_or_else(|| ParseError::end_of_input(input, "a comma separated sequence"))?;
+
+        let mut steps = Vec::<Step>::new();
+        for text in l.text.split(',') {
+            if let Some((i, c)) = text.char_indices().find(|(_, c)| !c.is_ascii()) {
+                return Err(l.error_at(&text[i..i + c.len_utf8()], "an ASCII character"));
             }
-        } else {
-            assert!(s.ends_with("-"));
-            let label = &s[0..s.len() - 1];
-            let hash = hash_str(label);
+            let (label, op) = match text.strip_suffix('-') {
+                Some(label) => (label, Op::Remove),
+                None => {
+                    let (label, fl) =
+                        l.split_once(text, "=", "`<label>=<focal length>` or `<label>-`")?;
+                    (label, Op::Insert(l.parse(fl, "a focal length")?))
+                }
+            };
+            steps.push(Step {
+                text: text.to_owned(),
+                label: label.to_owned(),
+                op,
+            });
+        }
+        if let Some(extra) = lines.next() {
+            return Err(extra.error_at(extra.text, "a single line"));
+        }
+        Ok(steps)
+    }
+
+    fn part1(steps: &Vec<Step>) -> usize {
+        steps.iter().map(|s| hash_str(&s.text)).sum()
+    }
+
+    fn part2(steps: &Vec<Step>) -> usize {
+        let mut boxes = Vec::<Vec<(String, usize)>>::new();
+        boxes.resize_with(256, Vec::<(String, usize)>::new);
 
+        for Step { label, op, .. } in steps.iter() {
+            let hash = hash_str(label);
             let b = &mut boxes[hash];
-            b.retain(|l| l.0 != label);
+            match *op {
+                Op::Insert(fl) => {
+                    let mut replaced = false;
+                    for l in b.iter_mut() {
+                        if l.0 == *label {
+                            l.1 = fl;
+                            replaced = true;
+                            break;
+                        }
+                    }
+                    if !replaced {
+                        b.push((label.to_owned(), fl));
+                    }
+                }
+                Op::Remove => b.retain(|l| l.0 != *label),
+            }
         }
-    }
 
-    let mut focusing_power = 0usize;
-    for (b_idx, b) in boxes.iter().enumerate() {
-        for (slot_idx, slot) in b.iter().enumerate() {
-            focusing_power += (1 + b_idx) * (1 + slot_idx) * slot.1;
+        let mut focusing_power = 0usize;
+        for (b_idx, b) in boxes.iter().enumerate() {
+            for (slot_idx, slot) in b.iter().enumerate() {
+                focusing_power += (1 + b_idx) * (1 + slot_idx) * slot.1;
+            }
         }
+        focusing_power
     }
 
-    (format!("{hashed_sum}"), format!("{focusing_power}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let steps = size.unwrap_or(4000);
+        let labels = (0..(steps / 8).max(1))
+            .map(|_| {
+                let len = rng.range(2..=6);
+                rng.string(LOWERCASE, len)
+            })
+            .collect::<Vec<_>>();
+        let steps = (0..steps)
+            .map(|_| {
+                let label = rng.pick(&labels);
+                if rng.chance(0.3) {
+                    format!("{label}-")
+                } else {
+                    format!("{label}={}", rng.range(1..=9))
+                }
+            })
+            .collect::<Vec<_>>();
+        steps.join(",") + "\n"
+    }
 }
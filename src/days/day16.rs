@@ -1,7 +1,17 @@
 use std::collections::HashSet;
 
+use rayon::prelude::*;
+
+use super::Solution;
+use crate::dir::Dir;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::render::{Palette, Rgb};
+use crate::trace::{self, Frame};
+
 #[derive(Debug, Clone, Copy)]
-enum Tile {
+pub enum Tile {
     Empty,
     MirrorForward,
     MirrorBackward,
@@ -9,12 +19,16 @@ enum Tile {
     SplitterVer,
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
+impl Tile {
+    fn symbol(&self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::MirrorForward => '/',
+            Tile::MirrorBackward => '\\',
+            Tile::SplitterHor => '-',
+            Tile::SplitterVer => '|',
+        }
+    }
 }
 
 fn progress(t: Tile, d: Dir) -> [Option<Dir>; 2] {
@@ -42,21 +56,7 @@ fn progress(t: Tile, d: Dir) -> [Option<Dir>; 2] {
     }
 }
 
-fn next_pos(x: usize, y: usize, d: Dir) -> (usize, usize) {
-    match d {
-        Dir::Up => (x, y.wrapping_sub(1)),
-        Dir::Down => (x, y.wrapping_add(1)),
-        Dir::Left => (x.wrapping_sub(1), y),
-        Dir::Right => (x.wrapping_add(1), y),
-    }
-}
-
-#[derive(Debug, Clone)]
-struct Grid {
-    rows: Vec<Vec<Tile>>,
-}
-
-fn calc_rays_set(g: &Grid, x: usize, y: usize, d: Dir) -> HashSet<(usize, usize, Dir)> {
+fn calc_rays_set(g: &Grid<Tile>, x: usize, y: usize, d: Dir) -> HashSet<(usize, usize, Dir)> {
     let mut rays = HashSet::<(usize, usize, Dir)>::new();
 
     let mut to_be_handled = Vec::<(usize, usize, Dir)>::new();
@@ -64,89 +64,115 @@ fn calc_rays_set(g: &Grid, x: usize, y: usize, d: Dir) -> HashSet<(usize, usize,
 
     let mut next_to_be_handled = Vec::<(usize, usize, Dir)>::new();
 
-    while to_be_handled.len() > 0 {
+    let mut wave = 0usize;
+    while !to_be_handled.is_empty() {
+        trace::record(|palette| {
+            let mut beams = g.map(Tile::symbol);
+            for &(x, y, _) in rays.iter() {
+                if matches!(g[(x, y)], Tile::Empty) {
+                    beams[(x, y)] = '#';
+                }
+            }
+            Frame::of_grid(
+                format_args!("beam from ({x}, {y}) going {d:?}, wave {wave}"),
+                &beams,
+                |c| match c {
+                    '#' => palette.highlight,
+                    '.' => palette.background,
+                    _ => palette.feature,
+                },
+            )
+        });
+        wave += 1;
         for (x, y, d) in to_be_handled.drain(..) {
-            let row = g.rows.get(y);
-            if let Some(row) = row {
-                let t = row.get(x);
-                if let Some(t) = t {
-                    if rays.insert((x, y, d)) {
-                        let next_dirs = progress(*t, d);
-                        for nd in next_dirs.iter() {
-                            if let Some(nd) = nd {
-                                let (nx, ny) = next_pos(x, y, *nd);
-                                next_to_be_handled.push((nx, ny, *nd));
-                            }
-                        }
+            if rays.insert((x, y, d)) {
+                let next_dirs = progress(g[(x, y)], d);
+                for nd in next_dirs.iter().flatten() {
+                    if let Some((nx, ny)) = g.step((x, y), *nd) {
+                        next_to_be_handled.push((nx, ny, *nd));
                     }
                 }
             }
         }
-        to_be_handled.extend(next_to_be_handled.drain(..));
+        to_be_handled.append(&mut next_to_be_handled);
     }
 
     rays
 }
 
-fn powered_count(g: &Grid, rays: &HashSet<(usize, usize, Dir)>) -> usize {
-    let mut powered_count = 0usize;
-    for (y, row) in g.rows.iter().enumerate() {
-        for (x, _) in row.iter().enumerate() {
-            for d in [Dir::Up, Dir::Down, Dir::Left, Dir::Right] {
-                if rays.contains(&(x, y, d)) {
-                    powered_count += 1;
-                    break;
-                }
-            }
-        }
-    }
-    powered_count
+fn powered_count(g: &Grid<Tile>, rays: &HashSet<(usize, usize, Dir)>) -> usize {
+    g.positions()
+        .filter(|&(x, y)| Dir::ALL.iter().any(|d| rays.contains(&(x, y, *d))))
+        .count()
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut rows = Vec::<Vec<Tile>>::new();
-    for l in input.lines() {
-        let row = l
-            .chars()
-            .map(|c| match c {
-                '.' => Tile::Empty,
-                '/' => Tile::MirrorForward,
-                '\\' => Tile::MirrorBackward,
-                '-' => Tile::SplitterHor,
-                '|' => Tile::SplitterVer,
-                other => panic!("{other}"),
-            })
-            .collect::<Vec<_>>();
-        rows.push(row);
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Grid<Tile>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+        Grid::parse(input, "a mirror, splitter or `.`", |c| match c {
+            '.' => Some(Tile::Empty),
+            '/' => Some(Tile::MirrorForward),
+            '\\' => Some(Tile::MirrorBackward),
+            '-' => Some(Tile::SplitterHor),
+            '|' => Some(Tile::SplitterVer),
+            _ => None,
+        })?
+        .non_empty(input)
     }
 
-    let g = Grid { rows };
-
-    let first_rays = calc_rays_set(&g, 0, 0, Dir::Right);
-    let first_powered_count = powered_count(&g, &first_rays);
-
-    let mut max_powered_count = 0usize;
-
-    let mut update_max_powered_count = |x: usize, y: usize, d: Dir| {
-        let rays = calc_rays_set(&g, x, y, d);
-        let powered_count = powered_count(&g, &rays);
+    fn part1(g: &Grid<Tile>) -> usize {
+        let first_rays = calc_rays_set(g, 0, 0, Dir::Right);
+        powered_count(g, &first_rays)
+    }
 
-        if powered_count > max_powered_count {
-            max_powered_count = powered_count;
+    fn part2(g: &Grid<Tile>) -> usize {
+        let mut entries = Vec::<(usize, usize, Dir)>::new();
+        for x in 0..g.width() {
+            entries.push((x, 0, Dir::Down));
+            entries.push((x, g.height() - 1, Dir::Up));
+        }
+        for y in 0..g.height() {
+            entries.push((0, y, Dir::Right));
+            entries.push((g.width() - 1, y, Dir::Left));
         }
-    };
 
-    for x in 0..g.rows[0].len() {
-        update_max_powered_count(x, 0, Dir::Down);
-        update_max_powered_count(x, g.rows.len() - 1, Dir::Up);
+        entries
+            .into_par_iter()
+            .map(|(x, y, d)| powered_count(g, &calc_rays_set(g, x, y, d)))
+            .max()
+            .unwrap_or(0)
     }
-    for y in 0..g.rows.len() {
-        update_max_powered_count(0, y, Dir::Right);
-        update_max_powered_count(g.rows[0].len() - 1, y, Dir::Left);
+
+    fn render(g: &Grid<Tile>, palette: &Palette) -> Option<Grid<Rgb>> {
+        let rays = calc_rays_set(g, 0, 0, Dir::Right);
+        let mut image = g.map(|t| match t {
+            Tile::Empty => palette.background,
+            _ => palette.feature,
+        });
+        for &(x, y, _) in rays.iter() {
+            if matches!(g[(x, y)], Tile::Empty) {
+                image[(x, y)] = palette.highlight;
+            }
+        }
+        Some(image)
     }
 
-    (
-        format!("{first_powered_count}"),
-        format!("{max_powered_count}"),
-    )
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let side = size.unwrap_or(110);
+        let cells = (0..side * side)
+            .map(|_| match rng.below(40) {
+                0 => '/',
+                1 => '\\',
+                2 => '|',
+                3 => '-',
+                _ => '.',
+            })
+            .collect();
+        Grid::new(side, side, cells).to_string()
+    }
 }
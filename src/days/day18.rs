@@ -1,19 +1,18 @@
 use std::collections::HashSet;
+use std::fmt::Display;
 
-use regex::Regex;
+use super::Solution;
+use crate::dir::{Dir, Pos};
+use crate::generate::{self, Rng};
+use crate::grid::Grid;
+use crate::log;
+use crate::parse::{self, ParseError};
+use crate::render::{Palette, Rgb};
 
 #[derive(Debug)]
-enum Dir {
-    U,
-    D,
-    L,
-    R,
-}
-
-#[derive(Debug)]
-struct Plan {
-    d: Dir,
-    l: usize,
+pub struct Plan {
+    pub d: Dir,
+    pub l: usize,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -24,125 +23,125 @@ enum Terrain {
     Exterior,
 }
 
-struct Ground {
-    rows: Vec<Vec<Terrain>>,
-}
-
-pub fn main(input: &str) -> (String, String) {
-    let re = Regex::new(r"(.) (\d+) \(#(.....)(.)\)").unwrap();
-
-    let mut plans = Vec::<Plan>::new();
-    let mut plans2 = Vec::<Plan>::new();
-    for l in input.lines() {
-        let captures = re.captures(l).unwrap();
-        let d = match captures.get(1).unwrap().as_str() {
-            "U" => Dir::U,
-            "D" => Dir::D,
-            "L" => Dir::L,
-            "R" => Dir::R,
-            other => panic!("{other}"),
-        };
-        let l = captures.get(2).unwrap().as_str().parse::<usize>().unwrap();
-
-        let l2 = usize::from_str_radix(captures.get(3).unwrap().as_str(), 16).unwrap();
-        let d2 = match captures.get(4).unwrap().as_str() {
-            "3" => Dir::U,
-            "1" => Dir::D,
-            "2" => Dir::L,
-            "0" => Dir::R,
-            other => panic!("{other}"),
+impl Display for Terrain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Terrain::Unknown => '?',
+            Terrain::Edge => '#',
+            Terrain::Interior => 'X',
+            Terrain::Exterior => '.',
         };
-
-        plans.push(Plan { d, l });
-        plans2.push(Plan { d: d2, l: l2 });
+        write!(f, "{c}")
     }
+}
 
-    let mut g = Ground {
-        rows: vec![vec![Terrain::Unknown]],
-    };
+#[derive(Debug)]
+pub struct DigPlan {
+    pub plans: Vec<Plan>,
+    pub plans2: Vec<Plan>,
+}
 
-    let mut pos = (0usize, 0usize);
+/// Digs out the trench for `plans`, then the lagoon inside it.
+fn dig(plans: &[Plan]) -> Grid<Terrain> {
+    let mut trench = vec![Pos::default()];
+    let mut pos = Pos::default();
     for p in plans.iter() {
-        let l = p.l;
-        for _ in 0..l {
-            pos = match p.d {
-                Dir::U => (pos.0, pos.1.wrapping_sub(1)),
-                Dir::D => (pos.0, pos.1 + 1),
-                Dir::L => (pos.0.wrapping_sub(1), pos.1),
-                Dir::R => (pos.0 + 1, pos.1),
-            };
-            if pos.0 == usize::MAX {
-                for row in g.rows.iter_mut() {
-                    row.insert(0, Terrain::Unknown);
-                }
-                pos.0 = 0;
-            }
-            while pos.0 >= g.rows[0].len() {
-                for row in g.rows.iter_mut() {
-                    row.push(Terrain::Unknown);
-                }
-            }
-            if pos.1 == usize::MAX {
-                g.rows.insert(0, vec![Terrain::Unknown; g.rows[0].len()]);
-                pos.1 = 0;
-            }
-            while pos.1 >= g.rows.len() {
-                g.rows.push(vec![Terrain::Unknown; g.rows[0].len()]);
-            }
-
-            g.rows[pos.1][pos.0] = Terrain::Edge;
+        for _ in 0..p.l {
+            pos = pos.step(p.d, 1);
+            trench.push(pos);
         }
     }
 
+    let min_x = trench.iter().map(|p| p.x).min().unwrap();
+    let max_x = trench.iter().map(|p| p.x).max().unwrap();
+    let min_y = trench.iter().map(|p| p.y).min().unwrap();
+    let max_y = trench.iter().map(|p| p.y).max().unwrap();
+    let mut g = Grid::filled(
+        (max_x - min_x + 1) as usize,
+        (max_y - min_y + 1) as usize,
+        Terrain::Unknown,
+    );
+    for p in trench {
+        g[Pos::new(p.x - min_x, p.y - min_y).to_unsigned().unwrap()] = Terrain::Edge;
+    }
+
     let mut any_changes = true;
     while any_changes {
         any_changes = false;
-        for y in 0..g.rows.len() {
-            for x in 0..g.rows[0].len() {
-                if !matches!(g.rows[y][x], Terrain::Unknown) {
-                    continue;
-                }
+        for pos in g.positions() {
+            if !matches!(g[pos], Terrain::Unknown) {
+                continue;
+            }
 
-                for (nx, ny) in [
-                    (x, y.wrapping_sub(1)),
-                    (x, y + 1),
-                    (x.wrapping_sub(1), y),
-                    (x + 1, y),
-                ] {
-                    let ot = g.rows.get(ny).and_then(|r| r.get(nx));
-                    if ot.is_none() || ot.is_some_and(|t| matches!(t, Terrain::Exterior)) {
-                        g.rows[y][x] = Terrain::Exterior;
-                        any_changes = true;
-                    }
+            for d in Dir::ALL {
+                let ot = g.step(pos, d).map(|n| g[n]);
+                if ot.is_none() || ot.is_some_and(|t| matches!(t, Terrain::Exterior)) {
+                    g[pos] = Terrain::Exterior;
+                    any_changes = true;
                 }
             }
         }
     }
 
-    for row in g.rows.iter_mut() {
-        for t in row.iter_mut() {
-            if matches!(t, Terrain::Unknown) {
-                *t = Terrain::Interior;
-            }
+    for t in g.cells_mut() {
+        if matches!(t, Terrain::Unknown) {
+            *t = Terrain::Interior;
         }
     }
 
-    for row in g.rows.iter() {
-        for t in row.iter() {
-            let c = match t {
-                Terrain::Unknown => '?',
-                Terrain::Edge => '#',
-                Terrain::Interior => 'X',
-                Terrain::Exterior => '.',
-            };
-            print!("{c}");
+    g
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = DigPlan;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<DigPlan, ParseError> {
+        let mut plans = Vec::<Plan>::new();
+        let mut plans2 = Vec::<Plan>::new();
+        for line in parse::lines(input) {
+            let expected = "`<dir> <length> (#<colour>)`";
+            let (d, rest) = line.split_once(line.text, " ", expected)?;
+            let (l, colour) = line.split_once(rest, " ", expected)?;
+            let d = d
+                .chars()
+                .next()
+                .and_then(Dir::from_letter)
+                .ok_or_else(|| line.error_at(d, "`U`, `D`, `L` or `R`"))?;
+            let l = line.parse::<usize>(l, "a length")?;
+
+            let colour = line.strip_prefix(colour, "(#")?;
+            let colour = line.strip_suffix(colour, ")")?;
+            if colour.len() != 6 || !colour.is_ascii() {
+                return Err(line.error_at(colour, "six hexadecimal digits"));
+            }
+            let (l2, d2) = colour.split_at(5);
+            let l2 = usize::from_str_radix(l2, 16)
+                .map_err(|_| line.error_at(l2, "a hexadecimal length"))?;
+            let d2 = d2
+                .chars()
+                .next()
+                .and_then(Dir::from_digit)
+                .ok_or_else(|| line.error_at(d2, "a direction digit from 0 to 3"))?;
+
+            plans.push(Plan { d, l });
+            plans2.push(Plan { d: d2, l: l2 });
         }
-        println!();
+
+        Ok(DigPlan { plans, plans2 })
     }
 
-    let mut part_1_count = 0usize;
-    for row in g.rows.iter() {
-        for t in row.iter() {
+    fn part1(dig_plan: &DigPlan) -> usize {
+        let g = dig(&dig_plan.plans);
+
+        log::info(|| format!("Lagoon fits in {}x{}", g.width(), g.height()));
+        log::debug(|| &g);
+
+        let mut part_1_count = 0usize;
+        for t in g.cells() {
             match t {
                 Terrain::Unknown => panic!(),
                 Terrain::Edge => part_1_count += 1,
@@ -150,100 +149,111 @@ pub fn main(input: &str) -> (String, String) {
                 Terrain::Exterior => (),
             };
         }
+
+        part_1_count
     }
 
-    // Calculate the extents of the plans
-    let mut min_x = 0isize;
-    let mut max_x = 0isize;
-    let mut min_y = 0isize;
-    let mut max_y = 0isize;
-
-    let mut hor_lines = Vec::<(isize, isize, isize)>::new();
-    let mut ver_lines = Vec::<(isize, isize, isize)>::new();
-    let mut pos = (0isize, 0isize);
-    for p in plans2.iter() {
-        let l = p.l as isize;
-        let prev_pos = pos;
-        pos = match p.d {
-            Dir::U => (pos.0, pos.1 - l),
-            Dir::D => (pos.0, pos.1 + l),
-            Dir::L => (pos.0 - l, pos.1),
-            Dir::R => (pos.0 + l, pos.1),
-        };
-        min_x = min_x.min(pos.0);
-        max_x = max_x.max(pos.0);
-        min_y = min_y.min(pos.1);
-        max_y = max_y.max(pos.1);
-
-        if prev_pos.0 == pos.0 {
-            let mut ys = [prev_pos.1, pos.1];
-            ys.sort();
-            ver_lines.push((pos.0, ys[0], ys[1]))
-        } else {
-            assert!(prev_pos.1 == pos.1);
-            let mut xs = [prev_pos.0, pos.0];
-            xs.sort();
-            hor_lines.push((pos.1, xs[0], xs[1]));
+    fn part2(dig_plan: &DigPlan) -> usize {
+        // Calculate the extents of the plans
+        let mut min_x = 0isize;
+        let mut max_x = 0isize;
+        let mut min_y = 0isize;
+        let mut max_y = 0isize;
+
+        let mut hor_lines = Vec::<(isize, isize, isize)>::new();
+        let mut ver_lines = Vec::<(isize, isize, isize)>::new();
+        let mut pos = Pos::default();
+        for p in dig_plan.plans2.iter() {
+            let prev_pos = pos;
+            pos = pos.step(p.d, p.l as isize);
+            min_x = min_x.min(pos.x);
+            max_x = max_x.max(pos.x);
+            min_y = min_y.min(pos.y);
+            max_y = max_y.max(pos.y);
+
+            if prev_pos.x == pos.x {
+                let mut ys = [prev_pos.y, pos.y];
+                ys.sort();
+                ver_lines.push((pos.x, ys[0], ys[1]))
+            } else {
+                assert!(prev_pos.y == pos.y);
+                let mut xs = [prev_pos.x, pos.x];
+                xs.sort();
+                hor_lines.push((pos.y, xs[0], xs[1]));
+            }
         }
-    }
 
-    hor_lines.sort();
-    ver_lines.sort();
+        hor_lines.sort();
+        ver_lines.sort();
 
-    let mut hor_set = HashSet::<(isize, isize, isize)>::new();
-    for hl in hor_lines.iter() {
-        hor_set.insert(*hl);
-    }
+        let mut hor_set = HashSet::<(isize, isize, isize)>::new();
+        for hl in hor_lines.iter() {
+            hor_set.insert(*hl);
+        }
 
-    let mut row_counts = Vec::<usize>::new();
-    for y in min_y..=max_y {
-        let mut count = 0usize;
+        let mut row_counts = Vec::<usize>::new();
+        for y in min_y..=max_y {
+            let mut count = 0usize;
 
-        let mut next_pair_is_inside = false;
-        let mut prev_line: Option<(isize, isize, isize)> = None;
-        for cur in ver_lines.iter().filter(|v| v.1 <= y && v.2 >= y) {
-            if prev_line.is_none() {
-                next_pair_is_inside = true;
-                prev_line = Some(*cur);
-                count += 1;
-                continue;
-            }
+            let mut next_pair_is_inside = false;
+            let mut prev_line: Option<(isize, isize, isize)> = None;
+            for cur in ver_lines.iter().filter(|v| v.1 <= y && v.2 >= y) {
+                if prev_line.is_none() {
+                    next_pair_is_inside = true;
+                    prev_line = Some(*cur);
+                    count += 1;
+                    continue;
+                }
 
-            let prev = prev_line.unwrap();
+                let prev = prev_line.unwrap();
 
-            let has_connecting_hor_line = hor_set.contains(&(y, prev.0, cur.0));
+                let has_connecting_hor_line = hor_set.contains(&(y, prev.0, cur.0));
 
-            if next_pair_is_inside || has_connecting_hor_line {
-                count += (cur.0 - prev.0) as usize;
-            } else {
-                count += 1;
-            }
+                if next_pair_is_inside || has_connecting_hor_line {
+                    count += (cur.0 - prev.0) as usize;
+                } else {
+                    count += 1;
+                }
 
-            if !has_connecting_hor_line {
-                next_pair_is_inside = !next_pair_is_inside;
-            } else {
-                let turns_back = cur.1 == prev.1 || cur.2 == prev.2;
-                if turns_back {
-                    if next_pair_is_inside {
-                        next_pair_is_inside = false;
-                    } else {
-                        next_pair_is_inside = true;
-                    }
+                if !has_connecting_hor_line {
+                    next_pair_is_inside = !next_pair_is_inside;
                 } else {
-                    if next_pair_is_inside {
-                        next_pair_is_inside = true;
-                    } else {
-                        next_pair_is_inside = false;
+                    let turns_back = cur.1 == prev.1 || cur.2 == prev.2;
+                    if turns_back {
+                        next_pair_is_inside = !next_pair_is_inside;
                     }
                 }
+                prev_line = Some(*cur);
             }
-            prev_line = Some(*cur);
+            assert!(!next_pair_is_inside);
+            row_counts.push(count);
         }
-        assert!(!next_pair_is_inside);
-        row_counts.push(count);
+
+        row_counts.iter().sum::<usize>()
     }
 
-    let part_2_count = row_counts.iter().sum::<usize>();
+    fn render(dig_plan: &DigPlan, palette: &Palette) -> Option<Grid<Rgb>> {
+        Some(dig(&dig_plan.plans).map(|t| match t {
+            Terrain::Edge => palette.highlight,
+            Terrain::Interior => palette.inside,
+            Terrain::Exterior => palette.outside,
+            Terrain::Unknown => palette.background,
+        }))
+    }
 
-    (format!("{part_1_count}"), format!("{part_2_count}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let blocks = size.unwrap_or(12).max(1);
+        // Going round a blob from the same state goes round the same blob, so the colours dig
+        // out the same shape again, only much bigger. Their lengths have five hex digits.
+        let mut again = rng.clone();
+        let plan = generate::closed_loop(rng, blocks, blocks, 2..=12);
+        let most = (0xfffff / (blocks * 2)).max(1);
+        let colours = generate::closed_loop(&mut again, blocks, blocks, (most / 4).max(1)..=most);
+
+        let mut out = String::new();
+        for ((d, n), (colour_d, colour_n)) in plan.into_iter().zip(colours) {
+            out += &format!("{} {n} (#{colour_n:05x}{})\n", d.letter(), colour_d.digit());
+        }
+        out
+    }
 }
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 
-use once_cell::sync::Lazy;
-use regex::Regex;
+use super::Solution;
+use crate::generate::{Rng, LOWERCASE};
+use crate::parse::{self, Line, ParseError};
 
 #[derive(Debug, Clone, Copy)]
-enum Category {
+pub enum Category {
     X,
     M,
     A,
@@ -12,38 +13,43 @@ enum Category {
 }
 
 impl Category {
-    fn new(s: &str) -> Category {
+    pub fn new(line: &Line, s: &str) -> Result<Category, ParseError> {
         match s {
-            "x" => Category::X,
-            "m" => Category::M,
-            "a" => Category::A,
-            "s" => Category::S,
-            other => panic!("{other}"),
+            "x" => Ok(Category::X),
+            "m" => Ok(Category::M),
+            "a" => Ok(Category::A),
+            "s" => Ok(Category::S),
+            _ => Err(line.error_at(s, "`x`, `m`, `a` or `s`")),
         }
     }
 }
 
+/// The index of each workflow by its name.
+type Names<'a> = HashMap<&'a str, usize>;
+
 #[derive(Debug)]
-enum Action {
-    Send { dst: String },
+pub enum Action {
+    /// On to the workflow at this index of [`System::workflows`].
+    Send { dst: usize },
     Accept,
     Reject,
 }
 
 impl Action {
-    fn new(s: &str) -> Action {
+    pub fn new(line: &Line, s: &str, names: &Names) -> Result<Action, ParseError> {
         match s {
-            "A" => Action::Accept,
-            "R" => Action::Reject,
-            other => Action::Send {
-                dst: other.to_owned(),
+            "A" => Ok(Action::Accept),
+            "R" => Ok(Action::Reject),
+            other => match names.get(other) {
+                Some(dst) => Ok(Action::Send { dst: *dst }),
+                None => Err(line.error_at(other, "`A`, `R` or the name of a workflow")),
             },
         }
     }
 }
 
 #[derive(Debug)]
-enum Rule {
+pub enum Rule {
     IfGreaterThan {
         cat: Category,
         val: usize,
@@ -59,31 +65,31 @@ enum Rule {
     },
 }
 
-static RULE_GT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w)>(\d+):(\w+)").unwrap());
-static RULE_LE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w)<(\d+):(\w+)").unwrap());
-
 impl Rule {
-    fn new(s: &str) -> Rule {
-        if let Some(c) = RULE_GT_RE.captures(s) {
+    pub fn new(line: &Line, s: &str, names: &Names) -> Result<Rule, ParseError> {
+        let Some((cond, act)) = s.split_once(':') else {
+            return Ok(Rule::Unconditionally {
+                act: Action::new(line, s, names)?,
+            });
+        };
+        let act = Action::new(line, act, names)?;
+        Ok(if let Some((cat, val)) = cond.split_once('>') {
             Rule::IfGreaterThan {
-                cat: Category::new(c.get(1).unwrap().as_str()),
-                val: c.get(2).unwrap().as_str().parse::<usize>().unwrap(),
-                act: Action::new(c.get(3).unwrap().as_str()),
-            }
-        } else if let Some(c) = RULE_LE_RE.captures(s) {
-            Rule::IfLessThan {
-                cat: Category::new(c.get(1).unwrap().as_str()),
-                val: c.get(2).unwrap().as_str().parse::<usize>().unwrap(),
-                act: Action::new(c.get(3).unwrap().as_str()),
+                cat: Category::new(line, cat)?,
+                val: line.parse::<usize>(val, "a rating")?,
+                act,
             }
         } else {
-            Rule::Unconditionally {
-                act: Action::new(s),
+            let (cat, val) = line.split_once(cond, "<", "`<category><<rating>` or `>`")?;
+            Rule::IfLessThan {
+                cat: Category::new(line, cat)?,
+                val: line.parse::<usize>(val, "a rating")?,
+                act,
             }
-        }
+        })
     }
 
-    fn apply(&self, part: &Part) -> Option<&Action> {
+    pub fn apply(&self, part: &Part) -> Option<&Action> {
         match self {
             Rule::IfGreaterThan { cat, val, act } => {
                 if part.get(*cat) > *val {
@@ -105,52 +111,68 @@ impl Rule {
 }
 
 #[derive(Debug)]
-struct Workflow {
-    name: String,
-    rules: Vec<Rule>,
+pub struct Workflow {
+    pub name: String,
+    pub rules: Vec<Rule>,
 }
 
-static WORKFLOW_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w+)\{(.+)\}").unwrap());
-
 impl Workflow {
-    fn new(s: &str) -> Workflow {
-        let c = WORKFLOW_RE.captures(s).unwrap();
-        let name = c.get(1).unwrap().as_str();
+    /// The name of the workflow on `line`, before its rules are parsed.
+    fn name<'a>(line: &Line<'a>) -> Result<&'a str, ParseError> {
+        Ok(line.split_once(line.text, "{", "`name{rules}`")?.0)
+    }
+
+    pub fn new(line: &Line, names: &Names) -> Result<Workflow, ParseError> {
+        let (name, rules_text) = line.split_once(line.text, "{", "`name{rules}`")?;
         let mut rules = Vec::<Rule>::new();
-        for rule in c.get(2).unwrap().as_str().split(",") {
-            rules.push(Rule::new(rule));
+        let mut last = "";
+        for rule in line.strip_suffix(rules_text, "}")?.split(',') {
+            rules.push(Rule::new(line, rule, names)?);
+            last = rule;
+        }
+        // Every part has to go somewhere once the conditions run out
+        if !matches!(rules.last(), Some(Rule::Unconditionally { .. })) {
+            return Err(line.error_at(last, "a last rule without a condition"));
         }
-        Workflow {
+        Ok(Workflow {
             name: name.to_owned(),
             rules,
-        }
+        })
     }
 }
 
-static PART_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\{x=(\d+),m=(\d+),a=(\d+),s=(\d+)\}").unwrap());
-
 #[derive(Debug, Clone, Copy)]
-struct Part {
-    x: usize,
-    m: usize,
-    a: usize,
-    s: usize,
+pub struct Part {
+    pub x: usize,
+    pub m: usize,
+    pub a: usize,
+    pub s: usize,
 }
 
 impl Part {
-    fn new(s: &str) -> Part {
-        let c = PART_RE.captures(s).unwrap();
-        let parse = |i: usize| -> usize { c.get(i).unwrap().as_str().parse::<usize>().unwrap() };
-        Part {
-            x: parse(1),
-            m: parse(2),
-            a: parse(3),
-            s: parse(4),
+    pub fn new(line: &Line) -> Result<Part, ParseError> {
+        let ratings = line.strip_prefix(line.text, "{")?;
+        let ratings = line.strip_suffix(ratings, "}")?;
+        let mut items = ratings.split(',');
+        let mut rating = |prefix: &str| {
+            let item = items
+                .next()
+                .ok_or_else(|| line.error_at(ratings, "`x=..,m=..,a=..,s=..`"))?;
+            line.parse::<usize>(line.strip_prefix(item, prefix)?, "a rating")
+        };
+        let part = Part {
+            x: rating("x=")?,
+            m: rating("m=")?,
+            a: rating("a=")?,
+            s: rating("s=")?,
+        };
+        if let Some(extra) = items.next() {
+            return Err(line.error_at(extra, "`}`"));
         }
+        Ok(part)
     }
 
-    fn get(&self, c: Category) -> usize {
+    pub fn get(&self, c: Category) -> usize {
         match c {
             Category::X => self.x,
             Category::M => self.m,
@@ -159,160 +181,231 @@ impl Part {
         }
     }
 
-    fn sum(&self) -> usize {
+    pub fn sum(&self) -> usize {
         self.x + self.m + self.a + self.s
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut workflows = Vec::<Workflow>::new();
-    let mut parts = Vec::<Part>::new();
+#[derive(Debug)]
+pub struct System {
+    pub workflows: Vec<Workflow>,
+    /// The index of the `in` workflow.
+    pub start: usize,
+    pub parts: Vec<Part>,
+}
 
-    let mut is_part_line = false;
-    for l in input.lines() {
-        if l.is_empty() {
-            is_part_line = true;
-            continue;
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = System;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<System, ParseError> {
+        let mut sections = parse::sections(input).into_iter();
+        let workflow_lines = sections.next().unwrap_or_default();
+        let mut names = Names::new();
+        for (i, line) in workflow_lines.iter().enumerate() {
+            let name = Workflow::name(line)?;
+            if names.insert(name, i).is_some() {
+                return Err(line.error_at(name, "a workflow name not used before"));
+            }
         }
-
-        if is_part_line {
-            parts.push(Part::new(l));
-        } else {
-            workflows.push(Workflow::new(l));
+        let start = *names
+            .get("in")
+            .ok_or_else(|| ParseError::whole_input("an `in` workflow", "none"))?;
+        let workflows = workflow_lines
+            .iter()
+            .map(|line| Workflow::new(line, &names))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parts = sections
+            .next()
+            .unwrap_or_default()
+            .iter()
+            .map(Part::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(extra) = sections.next() {
+            return Err(extra[0].error_at(extra[0].text, "end of input"));
         }
+
+        Ok(System {
+            workflows,
+            start,
+            parts,
+        })
     }
 
-    let name_to_wf = workflows
-        .iter()
-        .map(|wf| (wf.name.as_str(), wf))
-        .collect::<HashMap<_, _>>();
-
-    let in_wf = name_to_wf["in"];
-
-    let mut accepted_sum = 0usize;
-    for part in parts.iter() {
-        let mut wf = in_wf;
-        let mut rule_idx = 0usize;
-        loop {
-            let rule = &wf.rules[rule_idx];
-            let act = rule.apply(part);
-            match act {
-                Some(act) => match act {
-                    Action::Send { dst } => {
-                        wf = &name_to_wf[dst.as_str()];
-                        rule_idx = 0;
-                    }
-                    Action::Accept => {
-                        accepted_sum += part.sum();
-                        break;
-                    }
-                    Action::Reject => {
-                        break;
-                    }
-                },
-                None => rule_idx += 1,
+    fn part1(system: &System) -> usize {
+        let in_wf = &system.workflows[system.start];
+
+        let mut accepted_sum = 0usize;
+        for part in system.parts.iter() {
+            let mut wf = in_wf;
+            let mut rule_idx = 0usize;
+            loop {
+                let rule = &wf.rules[rule_idx];
+                let act = rule.apply(part);
+                match act {
+                    Some(act) => match act {
+                        Action::Send { dst } => {
+                            wf = &system.workflows[*dst];
+                            rule_idx = 0;
+                        }
+                        Action::Accept => {
+                            accepted_sum += part.sum();
+                            break;
+                        }
+                        Action::Reject => {
+                            break;
+                        }
+                    },
+                    None => rule_idx += 1,
+                }
             }
         }
-    }
 
-    #[derive(Debug, Clone, Copy)]
-    struct Ranges {
-        x: (usize, usize),
-        m: (usize, usize),
-        a: (usize, usize),
-        s: (usize, usize),
+        accepted_sum
     }
 
-    impl Ranges {
-        fn split(&self, c: Category, v: usize) -> (Ranges, Ranges) {
-            let mut r1 = *self;
-            let mut r2 = *self;
-            let (a, b, c) = match c {
-                Category::X => (self.x, &mut r1.x, &mut r2.x),
-                Category::M => (self.m, &mut r1.m, &mut r2.m),
-                Category::A => (self.a, &mut r1.a, &mut r2.a),
-                Category::S => (self.s, &mut r1.s, &mut r2.s),
-            };
-
-            let clamped = v.clamp(a.0, a.1);
-            b.1 = clamped;
-            c.0 = clamped;
-
-            (r1, r2)
+    fn part2(system: &System) -> usize {
+        let in_wf = &system.workflows[system.start];
+
+        #[derive(Debug, Clone, Copy)]
+        struct Ranges {
+            x: (usize, usize),
+            m: (usize, usize),
+            a: (usize, usize),
+            s: (usize, usize),
         }
 
-        fn combinations(&self) -> usize {
-            let combos = |v: (usize, usize)| -> usize { v.1 - v.0 };
-            combos(self.x) * combos(self.m) * combos(self.a) * combos(self.s)
+        impl Ranges {
+            fn split(&self, c: Category, v: usize) -> (Ranges, Ranges) {
+                let mut r1 = *self;
+                let mut r2 = *self;
+                let (a, b, c) = match c {
+                    Category::X => (self.x, &mut r1.x, &mut r2.x),
+                    Category::M => (self.m, &mut r1.m, &mut r2.m),
+                    Category::A => (self.a, &mut r1.a, &mut r2.a),
+                    Category::S => (self.s, &mut r1.s, &mut r2.s),
+                };
+
+                let clamped = v.clamp(a.0, a.1);
+                b.1 = clamped;
+                c.0 = clamped;
+
+                (r1, r2)
+            }
+
+            fn combinations(&self) -> usize {
+                let combos = |v: (usize, usize)| -> usize { v.1 - v.0 };
+                combos(self.x) * combos(self.m) * combos(self.a) * combos(self.s)
+            }
         }
-    }
 
-    struct RuleHead<'a> {
-        wf: &'a Workflow,
-        rule_idx: usize,
-        ranges: Ranges,
-    }
+        struct RuleHead<'a> {
+            wf: &'a Workflow,
+            rule_idx: usize,
+            ranges: Ranges,
+        }
 
-    let mut heads = Vec::<RuleHead>::new();
-
-    heads.push(RuleHead {
-        wf: in_wf,
-        rule_idx: 0,
-        ranges: Ranges {
-            x: (1, 4001),
-            m: (1, 4001),
-            a: (1, 4001),
-            s: (1, 4001),
-        },
-    });
-
-    let mut acc_ranges = Vec::<Ranges>::new();
-    let mut rej_ranges = Vec::<Ranges>::new();
-
-    let mut next_heads = Vec::<RuleHead>::new();
-    while !heads.is_empty() {
-        for h in heads.drain(..) {
-            let rule = &h.wf.rules[h.rule_idx];
-
-            let mut handle_action = |act: Option<&Action>, r: Ranges| match act {
-                Some(act) => match act {
-                    Action::Send { dst } => next_heads.push(RuleHead {
-                        wf: &name_to_wf[dst.as_str()],
-                        rule_idx: 0,
+        let mut heads = vec![RuleHead {
+            wf: in_wf,
+            rule_idx: 0,
+            ranges: Ranges {
+                x: (1, 4001),
+                m: (1, 4001),
+                a: (1, 4001),
+                s: (1, 4001),
+            },
+        }];
+
+        let mut acc_ranges = Vec::<Ranges>::new();
+        let mut rej_ranges = Vec::<Ranges>::new();
+
+        let mut next_heads = Vec::<RuleHead>::new();
+        while !heads.is_empty() {
+            for h in heads.drain(..) {
+                let rule = &h.wf.rules[h.rule_idx];
+
+                let mut handle_action = |act: Option<&Action>, r: Ranges| match act {
+                    Some(act) => match act {
+                        Action::Send { dst } => next_heads.push(RuleHead {
+                            wf: &system.workflows[*dst],
+                            rule_idx: 0,
+                            ranges: r,
+                        }),
+                        Action::Accept => {
+                            acc_ranges.push(r);
+                        }
+                        Action::Reject => {
+                            rej_ranges.push(r);
+                        }
+                    },
+                    None => next_heads.push(RuleHead {
+                        wf: h.wf,
+                        rule_idx: h.rule_idx + 1,
                         ranges: r,
                     }),
-                    Action::Accept => {
-                        acc_ranges.push(r);
+                };
+
+                match rule {
+                    Rule::IfGreaterThan { cat, val, act } => {
+                        let (no, yes) = h.ranges.split(*cat, *val + 1);
+                        handle_action(Some(act), yes);
+                        handle_action(None, no);
                     }
-                    Action::Reject => {
-                        rej_ranges.push(r);
+                    Rule::IfLessThan { cat, val, act } => {
+                        let (yes, no) = h.ranges.split(*cat, *val);
+                        handle_action(Some(act), yes);
+                        handle_action(None, no);
                     }
-                },
-                None => next_heads.push(RuleHead {
-                    wf: h.wf,
-                    rule_idx: h.rule_idx + 1,
-                    ranges: r,
-                }),
-            };
-
-            match rule {
-                Rule::IfGreaterThan { cat, val, act } => {
-                    let (no, yes) = h.ranges.split(*cat, *val + 1);
-                    handle_action(Some(act), yes);
-                    handle_action(None, no);
-                }
-                Rule::IfLessThan { cat, val, act } => {
-                    let (yes, no) = h.ranges.split(*cat, *val);
-                    handle_action(Some(act), yes);
-                    handle_action(None, no);
+                    Rule::Unconditionally { act } => handle_action(Some(act), h.ranges),
                 }
-                Rule::Unconditionally { act } => handle_action(Some(act), h.ranges),
             }
+            heads.append(&mut next_heads);
         }
-        heads.extend(next_heads.drain(..));
+
+        acc_ranges.iter().map(|r| r.combinations()).sum::<usize>()
     }
 
-    let combinations = acc_ranges.iter().map(|r| r.combinations()).sum::<usize>();
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let workflows = size.unwrap_or(550).max(1);
+        let mut names = rng.names(workflows, 3, LOWERCASE);
+        names[0] = "in".to_owned();
+
+        // Workflows only send parts on to later ones, so every part is accepted or rejected
+        let mut children = vec![Vec::<usize>::new(); workflows];
+        for i in 1..workflows {
+            children[rng.below(i)].push(i);
+        }
+        let mut lines = Vec::<String>::new();
+        for (name, children) in names.iter().zip(children) {
+            let mut targets = children
+                .iter()
+                .map(|c| names[*c].clone())
+                .collect::<Vec<_>>();
+            while targets.len() < 2 || (targets.len() < 4 && rng.chance(0.3)) {
+                targets.push(rng.pick(&["A", "R"]).to_string());
+            }
+            rng.shuffle(&mut targets);
+            let fallback = targets.pop().unwrap();
+            let rules = targets
+                .iter()
+                .map(|target| {
+                    let category = *rng.pick(b"xmas") as char;
+                    let op = *rng.pick(b"<>") as char;
+                    format!("{category}{op}{}:{target}", rng.range(1..=4000))
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!("{name}{{{},{fallback}}}\n", rules.join(",")));
+        }
+        rng.shuffle(&mut lines);
 
-    (format!("{accepted_sum}"), format!("{combinations}"))
+        let mut parts = String::new();
+        for _ in 0..(workflows * 200 / 550).max(1) {
+            let [x, m, a, s] = [0; 4].map(|_| rng.range(1..=4000));
+            parts += &format!("{{x={x},m={m},a={a},s={s}}}\n");
+        }
+        format!("{}\n{parts}", lines.concat())
+    }
 }
@@ -1,26 +1,36 @@
-use regex::Regex;
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
 
 #[derive(Debug)]
-struct Subset {
-    red: usize,
-    green: usize,
-    blue: usize,
+pub struct Subset {
+    pub red: usize,
+    pub green: usize,
+    pub blue: usize,
 }
 
 #[derive(Debug)]
-struct Game {
-    game_id: usize,
-    subsets: Vec<Subset>,
+pub struct Game {
+    pub game_id: usize,
+    pub subsets: Vec<Subset>,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let game_re = Regex::new(r"Game (\d+): (.*)").unwrap();
-    let grab_re = Regex::new(r"(\d+) (.+)").unwrap();
-    let mut games = Vec::<Game>::new();
-    for l in input.lines() {
-        for game in game_re.captures_iter(l) {
-            let game_id = game.get(1).unwrap().as_str().parse::<usize>().unwrap();
-            let rounds = game.get(2).unwrap().as_str();
+const MAX_RED: usize = 12;
+const MAX_GREEN: usize = 13;
+const MAX_BLUE: usize = 14;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<Game>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Game>, ParseError> {
+        let mut games = Vec::<Game>::new();
+        for line in parse::lines(input) {
+            let (game, rounds) = line.key_values("`Game <id>: <rounds>`")?;
+            let game_id = line.parse::<usize>(line.strip_prefix(game, "Game ")?, "a game id")?;
 
             let mut subsets = Vec::<Subset>::new();
 
@@ -29,55 +39,77 @@ pub fn main(input: &str) -> (String, String) {
                 let mut green = 0usize;
                 let mut blue = 0usize;
                 for grab in round.split(", ") {
-                    for cubes in grab_re.captures_iter(grab) {
-                        let count = cubes.get(1).unwrap().as_str().parse::<usize>().unwrap();
-                        match cubes.get(2).unwrap().as_str() {
-                            "red" => red = count,
-                            "green" => green = count,
-                            "blue" => blue = count,
-                            other => panic!("{other}"),
-                        };
-                    }
+                    let (count, colour) = line.split_once(grab, " ", "`<count> <colour>`")?;
+                    let count = line.parse::<usize>(count, "a count")?;
+                    match colour {
+                        "red" => red = count,
+                        "green" => green = count,
+                        "blue" => blue = count,
+                        _ => return Err(line.error_at(colour, "`red`, `green` or `blue`")),
+                    };
                 }
                 subsets.push(Subset { red, green, blue })
             }
             games.push(Game { game_id, subsets })
         }
+        Ok(games)
     }
 
-    const MAX_RED: usize = 12;
-    const MAX_GREEN: usize = 13;
-    const MAX_BLUE: usize = 14;
+    fn part1(games: &Vec<Game>) -> usize {
+        let mut possible_game_id_sum = 0usize;
 
-    let mut possible_game_id_sum = 0usize;
-
-    for g in games.iter() {
-        let mut any_impossible = false;
-        for s in g.subsets.iter() {
-            let is_possible = s.red <= MAX_RED && s.green <= MAX_GREEN && s.blue <= MAX_BLUE;
-            if !is_possible {
-                any_impossible = true;
+        for g in games.iter() {
+            let mut any_impossible = false;
+            for s in g.subsets.iter() {
+                let is_possible = s.red <= MAX_RED && s.green <= MAX_GREEN && s.blue <= MAX_BLUE;
+                if !is_possible {
+                    any_impossible = true;
+                }
+            }
+            if !any_impossible {
+                possible_game_id_sum += g.game_id;
             }
         }
-        if !any_impossible {
-            possible_game_id_sum += g.game_id;
-        }
+        possible_game_id_sum
     }
 
-    let mut game_power_sum = 0usize;
+    fn part2(games: &Vec<Game>) -> usize {
+        let mut game_power_sum = 0usize;
 
-    for g in games.iter() {
-        let mut min_red = 0usize;
-        let mut min_green = 0usize;
-        let mut min_blue = 0usize;
-        for s in g.subsets.iter() {
-            min_red = usize::max(min_red, s.red);
-            min_green = usize::max(min_green, s.green);
-            min_blue = usize::max(min_blue, s.blue);
+        for g in games.iter() {
+            let mut min_red = 0usize;
+            let mut min_green = 0usize;
+            let mut min_blue = 0usize;
+            for s in g.subsets.iter() {
+                min_red = usize::max(min_red, s.red);
+                min_green = usize::max(min_green, s.green);
+                min_blue = usize::max(min_blue, s.blue);
+            }
+            let game_power = min_red * min_green * min_blue;
+            game_power_sum += game_power;
         }
-        let game_power = min_red * min_green * min_blue;
-        game_power_sum += game_power;
+
+        game_power_sum
     }
 
-    (format!("{possible_game_id_sum}"), format!("{game_power_sum}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let mut out = String::new();
+        for game_id in 1..=size.unwrap_or(100) {
+            let rounds = (0..rng.range(1..=6))
+                .map(|_| {
+                    let mut colours = ["red", "green", "blue"];
+                    rng.shuffle(&mut colours);
+                    let grabs = rng.range(1..=3);
+                    colours[..grabs]
+                        .iter()
+                        .map(|colour| format!("{} {colour}", rng.range(1..=20)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            out += &format!("Game {game_id}: {rounds}\n");
+        }
+        out
+    }
 }
@@ -1,17 +1,24 @@
 use std::collections::{HashMap, HashSet};
 
+use super::{Comparison, MaybeAnswer, Solution};
+use crate::generate::{Rng, LOWERCASE};
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+use crate::render::{Palette, Rgb};
+use crate::trace::{self, Frame};
+
 #[derive(Debug, Clone, Copy)]
-enum ModuleType {
+pub enum ModuleType {
     None,
     Conjunction,
     FlipFlop,
 }
 
 #[derive(Debug)]
-struct Module {
-    t: ModuleType,
-    n: String,
-    d: Vec<String>,
+pub struct Module {
+    pub t: ModuleType,
+    pub n: String,
+    pub d: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -61,6 +68,20 @@ impl ModuleState {
     }
 }
 
+impl ModuleState {
+    fn colour(&self, palette: &Palette) -> Rgb {
+        match self {
+            ModuleState::None => palette.feature,
+            ModuleState::FlipFlop(State::High) => palette.highlight,
+            ModuleState::FlipFlop(State::Low) => palette.background,
+            ModuleState::Conjunction(s) if s.values().all(|v| matches!(v, State::High)) => {
+                palette.inside
+            }
+            ModuleState::Conjunction(_) => palette.outside,
+        }
+    }
+}
+
 struct LiveModule<'a> {
     #[allow(dead_code)]
     module: &'a Module,
@@ -68,200 +89,315 @@ struct LiveModule<'a> {
     dst_idxs: Vec<usize>,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut modules = Vec::<Module>::new();
-
-    modules.push(Module {
-        t: ModuleType::None,
-        n: "button".to_owned(),
-        d: vec!["broadcaster".to_owned()],
-    });
-
-    for l in input.lines() {
-        let mut split = l.split(" -> ");
-        let src = split.next().unwrap();
-        let dst = split.next().unwrap();
-        let dsts: Vec<String> = dst.split(", ").map(|s| s.to_owned()).collect::<Vec<_>>();
-
-        let module = if src.starts_with("%") {
-            Module {
-                t: ModuleType::FlipFlop,
-                n: src[1..].to_owned(),
-                d: dsts,
+fn input_idxes(name: &str, modules: &[Module]) -> Vec<usize> {
+    let mut idxes = Vec::<usize>::new();
+    for (n_module_idx, n_module) in modules.iter().enumerate() {
+        for dst in n_module.d.iter() {
+            if dst.as_str() == name {
+                idxes.push(n_module_idx);
             }
-        } else if src.starts_with("&") {
-            Module {
-                t: ModuleType::Conjunction,
-                n: src[1..].to_owned(),
-                d: dsts,
-            }
-        } else {
-            Module {
-                t: ModuleType::None,
-                n: src.to_owned(),
-                d: dsts,
-            }
-        };
+        }
+    }
+    idxes
+}
+
+struct Machine<'a> {
+    modules: &'a [Module],
+    name_to_idx: HashMap<&'a str, usize>,
+    live_modules: Vec<LiveModule<'a>>,
+    presses: usize,
+}
+
+impl<'a> Machine<'a> {
+    fn new(modules: &'a [Module]) -> Machine<'a> {
+        let name_to_idx = modules
+            .iter()
+            .enumerate()
+            .map(|m| (m.1.n.as_str(), m.0))
+            .collect::<HashMap<_, _>>();
+
+        let mut live_modules = Vec::<LiveModule>::new();
+
+        for module in modules.iter() {
+            let state = match module.t {
+                ModuleType::None => ModuleState::None,
+                ModuleType::Conjunction => {
+                    let hm = input_idxes(&module.n, modules)
+                        .iter()
+                        .map(|n| (*n, State::Low))
+                        .collect::<HashMap<_, _>>();
+                    ModuleState::Conjunction(hm)
+                }
+                ModuleType::FlipFlop => ModuleState::FlipFlop(State::Low),
+            };
+            let dst_idxs = module
+                .d
+                .iter()
+                .map(|s| name_to_idx[s.as_str()])
+                .collect::<Vec<_>>();
+            live_modules.push(LiveModule {
+                module,
+                state,
+                dst_idxs,
+            });
+        }
 
-        modules.push(module);
+        Machine {
+            modules,
+            name_to_idx,
+            live_modules,
+            presses: 0,
+        }
     }
 
-    let name_to_idx = modules
-        .iter()
-        .enumerate()
-        .map(|m| (m.1.n.as_str(), m.0))
-        .collect::<HashMap<_, _>>();
+    /// Pushes the button once, calling `on_pulse` with the destination index and state of every pulse sent.
+    fn push_button<F>(&mut self, mut on_pulse: F)
+    where
+        F: FnMut(usize, State),
+    {
+        let button_idx = self.name_to_idx["button"];
+        let broadcaster_idx = self.name_to_idx["broadcaster"];
 
-    let mut output_modules = HashSet::<String>::new();
+        let mut pulses = Vec::<(usize, usize, State, &str, &str)>::new();
+        let mut next_pulses = Vec::<(usize, usize, State, &str, &str)>::new();
 
-    for module in modules.iter() {
-        for dst in module.d.iter() {
-            if !name_to_idx.contains_key(dst.as_str()) {
-                output_modules.insert(dst.clone());
+        pulses.push((
+            button_idx,
+            broadcaster_idx,
+            State::Low,
+            "button",
+            "broadcaster",
+        ));
+        self.presses += 1;
+        let mut wave = 0usize;
+        while !pulses.is_empty() {
+            trace::record(|palette| {
+                let text = pulses
+                    .iter()
+                    .map(|(_, _, state, src, dst)| {
+                        let state = match state {
+                            State::High => "high",
+                            State::Low => "low",
+                        };
+                        format!("{src} -{state}-> {dst}\n")
+                    })
+                    .collect::<String>();
+                let states = self
+                    .live_modules
+                    .iter()
+                    .map(|m| m.state.colour(palette))
+                    .collect::<Vec<_>>();
+                Frame {
+                    picture: Some(Grid::new(states.len(), 1, states)),
+                    ..Frame::new(format_args!("press {}, wave {wave}", self.presses), text)
+                }
+            });
+            wave += 1;
+            for (src_idx, dst_idx, in_state, _src_name, _dst_name) in pulses.drain(..) {
+                on_pulse(dst_idx, in_state);
+
+                let dst_name = self.modules[dst_idx].n.as_str();
+
+                let out_state = self.live_modules[dst_idx].state.update(src_idx, in_state);
+
+                if let Some(out_state) = out_state {
+                    for next_dst_idx in self.live_modules[dst_idx].dst_idxs.iter() {
+                        let next_dst_name = self.modules[*next_dst_idx].n.as_str();
+                        next_pulses.push((
+                            dst_idx,
+                            *next_dst_idx,
+                            out_state,
+                            dst_name,
+                            next_dst_name,
+                        ));
+                    }
+                }
             }
+            pulses.append(&mut next_pulses);
         }
     }
+}
 
-    modules.extend(output_modules.drain().map(|n| Module {
-        t: ModuleType::None,
-        n,
-        d: vec![],
-    }));
+fn is_odd_prime(n: usize) -> bool {
+    n > 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
 
-    let name_to_idx = modules
+fn join(names: &[&String]) -> String {
+    names
         .iter()
-        .enumerate()
-        .map(|m| (m.1.n.as_str(), m.0))
-        .collect::<HashMap<_, _>>();
-
-    let mut live_modules = Vec::<LiveModule>::new();
-
-    fn input_idxes(name: &str, modules: &Vec<Module>) -> Vec<usize> {
-        let mut idxes = Vec::<usize>::new();
-        for (n_module_idx, n_module) in modules.iter().enumerate() {
-            for dst in n_module.d.iter() {
-                if dst.as_str() == name {
-                    idxes.push(n_module_idx);
-                }
-            }
+        .map(|n| n.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The most presses [`reference_presses`] makes before giving up, and part 2 makes waiting for its
+/// conjunctions to cycle.
+const REFERENCE_PRESSES: usize = 10_000_000;
+
+/// Part 2 by pressing the button until `rx` gets a low pulse, or `None` if that takes too long.
+fn reference_presses(modules: &[Module]) -> Option<MaybeAnswer<usize>> {
+    let mut machine = Machine::new(modules);
+    let Some(&rx_idx) = machine.name_to_idx.get("rx") else {
+        return Some(MaybeAnswer(None));
+    };
+    for presses in 1..=REFERENCE_PRESSES {
+        let mut low_to_rx = false;
+        machine.push_button(|dst_idx, in_state| {
+            low_to_rx |= dst_idx == rx_idx && matches!(in_state, State::Low);
+        });
+        if low_to_rx {
+            return Some(MaybeAnswer(Some(presses)));
         }
-        idxes
     }
+    None
+}
 
-    for module in modules.iter() {
-        let state = match module.t {
-            ModuleType::None => ModuleState::None,
-            ModuleType::Conjunction => {
-                let hm = input_idxes(&module.n, &modules)
-                    .iter()
-                    .map(|n| (*n, State::Low))
-                    .collect::<HashMap<_, _>>();
-                ModuleState::Conjunction(hm)
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<Module>;
+    type Answer1 = usize;
+    type Answer2 = MaybeAnswer<usize>;
+
+    fn parse(input: &str) -> Result<Vec<Module>, ParseError> {
+        let mut modules = Vec::<Module>::new();
+
+        modules.push(Module {
+            t: ModuleType::None,
+            n: "button".to_owned(),
+            d: vec!["broadcaster".to_owned()],
+        });
+
+        let mut defined = HashSet::<&str>::new();
+        for line in parse::lines(input) {
+            let (src, dsts) = line.adjacency()?;
+            let dsts = dsts.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+
+            let name = src.trim_start_matches(['%', '&']);
+            // The button is added above, wired to the broadcaster
+            if name == "button" {
+                return Err(line.error_at(name, "a module name other than `button`"));
             }
-            ModuleType::FlipFlop => ModuleState::FlipFlop(State::Low),
-        };
-        let dst_idxs = module
-            .d
+            if !defined.insert(name) {
+                return Err(line.error_at(name, "a module name not used before"));
+            }
+
+            let module = if let Some(name) = src.strip_prefix('%') {
+                Module {
+                    t: ModuleType::FlipFlop,
+                    n: name.to_owned(),
+                    d: dsts,
+                }
+            } else if let Some(name) = src.strip_prefix('&') {
+                Module {
+                    t: ModuleType::Conjunction,
+                    n: name.to_owned(),
+                    d: dsts,
+                }
+            } else {
+                Module {
+                    t: ModuleType::None,
+                    n: src.to_owned(),
+                    d: dsts,
+                }
+            };
+
+            modules.push(module);
+        }
+        if !defined.contains("broadcaster") {
+            return Err(ParseError::whole_input("a `broadcaster` module", "none"));
+        }
+
+        let name_to_idx = modules
             .iter()
-            .map(|s| name_to_idx[s.as_str()])
-            .collect::<Vec<_>>();
-        live_modules.push(LiveModule {
-            module,
-            state,
-            dst_idxs,
-        });
+            .enumerate()
+            .map(|m| (m.1.n.as_str(), m.0))
+            .collect::<HashMap<_, _>>();
+
+        let mut output_modules = HashSet::<String>::new();
+
+        for module in modules.iter() {
+            for dst in module.d.iter() {
+                if !name_to_idx.contains_key(dst.as_str()) {
+                    output_modules.insert(dst.clone());
+                }
+            }
+        }
+
+        modules.extend(output_modules.drain().map(|n| Module {
+            t: ModuleType::None,
+            n,
+            d: vec![],
+        }));
+
+        Ok(modules)
     }
 
-    let has_rx = name_to_idx.contains_key("rx");
+    fn part1(modules: &Vec<Module>) -> usize {
+        let mut machine = Machine::new(modules);
 
-    let needs_low_iteration_record = if has_rx {
-        let n0 = input_idxes("rx", &modules);
+        let mut low_pulse_count = 0usize;
+        let mut high_pulse_count = 0usize;
+        for _ in 0..1000 {
+            machine.push_button(|_, in_state| match in_state {
+                State::High => high_pulse_count += 1,
+                State::Low => low_pulse_count += 1,
+            });
+        }
+        low_pulse_count * high_pulse_count
+    }
+
+    fn part2(modules: &Vec<Module>) -> MaybeAnswer<usize> {
+        let mut machine = Machine::new(modules);
+
+        if !machine.name_to_idx.contains_key("rx") {
+            return MaybeAnswer(None);
+        }
+
+        let n0 = input_idxes("rx", modules);
 
         let get_next_input_idxes = |idxs: &Vec<usize>| -> Vec<usize> {
             let mut next_input_idxes = Vec::<usize>::new();
             for idx in idxs.iter() {
-                next_input_idxes.extend(input_idxes(&modules[*idx].n, &modules));
+                next_input_idxes.extend(input_idxes(&modules[*idx].n, modules));
             }
             next_input_idxes
         };
 
-        let n1 = get_next_input_idxes(&n0);
-
-        n1
-    } else {
-        vec![]
-    };
-
-    let button_idx = name_to_idx["button"];
-    let broadcaster_idx = name_to_idx["broadcaster"];
-
-    let mut pulses = Vec::<(usize, usize, State, &str, &str)>::new();
-    let mut next_pulses = Vec::<(usize, usize, State, &str, &str)>::new();
-
-    let mut low_iterations = modules
-        .iter()
-        .map(|_| Vec::<usize>::new())
-        .collect::<Vec<_>>();
+        let needs_low_iteration_record = get_next_input_idxes(&n0);
+        // With nothing two modules back from `rx` there are no cycles to multiply
+        if needs_low_iteration_record.is_empty() {
+            return MaybeAnswer(None);
+        }
 
-    let mut low_pulse_count = 0usize;
-    let mut high_pulse_count = 0usize;
-    let mut button_push_idx = 0usize;
-    loop {
-        pulses.push((
-            button_idx,
-            broadcaster_idx,
-            State::Low,
-            "button",
-            "broadcaster",
-        ));
-        while !pulses.is_empty() {
-            for (src_idx, dst_idx, in_state, _src_name, _dst_name) in pulses.drain(..) {
-                if button_push_idx < 1000 {
-                    match in_state {
-                        State::High => high_pulse_count += 1,
-                        State::Low => low_pulse_count += 1,
-                    }
-                }
+        let mut low_iterations = modules
+            .iter()
+            .map(|_| Vec::<usize>::new())
+            .collect::<Vec<_>>();
 
+        let mut button_push_idx = 0usize;
+        loop {
+            if button_push_idx == REFERENCE_PRESSES {
+                return MaybeAnswer(None);
+            }
+            machine.push_button(|dst_idx, in_state| {
                 if matches!(in_state, State::Low) {
                     low_iterations[dst_idx].push(button_push_idx);
                 }
+            });
+            button_push_idx += 1;
 
-                let dst_name = modules[dst_idx].n.as_str();
-
-                let out_state = live_modules[dst_idx].state.update(src_idx, in_state);
-
-                match out_state {
-                    Some(out_state) => {
-                        for next_dst_idx in live_modules[dst_idx].dst_idxs.iter() {
-                            let next_dst_name = modules[*next_dst_idx].n.as_str();
-                            next_pulses.push((
-                                dst_idx,
-                                *next_dst_idx,
-                                out_state,
-                                dst_name,
-                                next_dst_name,
-                            ));
-                        }
-                    }
-                    None => (),
-                }
-            }
-            pulses.extend(next_pulses.drain(..));
-        }
-        button_push_idx += 1;
-
-        if button_push_idx >= 1000
-            && needs_low_iteration_record
+            if needs_low_iteration_record
                 .iter()
                 .all(|n| low_iterations[*n].len() >= 3)
-        {
-            break;
+            {
+                break;
+            }
         }
-    }
-    let product = low_pulse_count * high_pulse_count;
 
-    let rx_low_pulse_min = if has_rx {
         let deltas = needs_low_iteration_record
             .iter()
             .map(|n| low_iterations[*n][2] - low_iterations[*n][1])
@@ -271,10 +407,63 @@ pub fn main(input: &str) -> (String, String) {
         for d in deltas {
             m *= d;
         }
-        m
-    } else {
-        usize::MAX
-    };
+        MaybeAnswer(Some(m))
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        // Each counter is a chain of flip-flops counting presses in binary, which a conjunction
+        // resets when they reach a prime. The real input has four counters of 12 flip-flops.
+        let bits = size.unwrap_or(12).clamp(3, 20);
+        let mut primes = ((1usize << (bits - 1))..(1 << bits))
+            .filter(|n| is_odd_prime(*n))
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut primes);
+        let counters = primes.len().min(4);
+
+        let names = rng.names(counters * (bits + 2) + 2, 2, LOWERCASE);
+        let mut names = names.into_iter().filter(|n| n != "rx");
+        let last = names.next().unwrap();
+
+        let mut lines = Vec::<String>::new();
+        let mut firsts = Vec::<String>::new();
+        for p in primes[..counters].iter() {
+            let flip_flops = names.by_ref().take(bits).collect::<Vec<_>>();
+            let (hub, inverter) = (names.next().unwrap(), names.next().unwrap());
+            let is_set = |i: usize| p & (1 << i) != 0;
+
+            for (i, f) in flip_flops.iter().enumerate() {
+                let mut dsts = flip_flops.get(i + 1).into_iter().collect::<Vec<_>>();
+                if is_set(i) {
+                    dsts.push(&hub);
+                }
+                rng.shuffle(&mut dsts);
+                lines.push(format!("%{f} -> {}\n", join(&dsts)));
+            }
+            // Setting the unset bits and adding one more carries the count round to zero
+            let mut dsts = vec![&inverter, &flip_flops[0]];
+            dsts.extend(
+                flip_flops
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !is_set(*i))
+                    .map(|(_, f)| f),
+            );
+            rng.shuffle(&mut dsts);
+            lines.push(format!("&{hub} -> {}\n", join(&dsts)));
+            lines.push(format!("&{inverter} -> {last}\n"));
+            firsts.push(flip_flops[0].clone());
+        }
+        lines.push(format!("broadcaster -> {}\n", firsts.join(", ")));
+        lines.push(format!("&{last} -> rx\n"));
+        rng.shuffle(&mut lines);
+        lines.concat()
+    }
 
-    (format!("{product}"), format!("{rx_low_pulse_min}"))
+    fn cross_check(modules: &Vec<Module>) -> Vec<Comparison> {
+        vec![Comparison::new(
+            "part 2",
+            Self::part2(modules),
+            reference_presses(modules),
+        )]
+    }
 }
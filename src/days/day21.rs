@@ -1,147 +1,222 @@
 use std::collections::HashSet;
 
-enum Tile {
+use super::{Comparison, Solution};
+use crate::dir::{Dir, Pos};
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::search;
+
+pub enum Tile {
     Start,
     Plot,
     Rock,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut rows = Vec::<Vec<Tile>>::new();
-    for l in input.lines() {
-        let mut row = Vec::<Tile>::new();
-        for c in l.chars() {
-            let tile = match c {
-                '.' => Tile::Plot,
-                '#' => Tile::Rock,
-                'S' => Tile::Start,
-                other => panic!("{other}"),
-            };
-            row.push(tile);
+fn find_start(g: &Grid<Tile>) -> (usize, usize) {
+    g.find(|t| matches!(t, Tile::Start)).unwrap()
+}
+
+fn calc_reachable_after_its(g: &Grid<Tile>, start: (usize, usize)) -> Vec<usize> {
+    let steps_to = search::breadth_first(start, |&pos| {
+        g.neighbours4(pos)
+            .filter(|&npos| !matches!(g[npos], Tile::Rock))
+    });
+
+    // A position reached in n steps can be reached again in n + 2 by stepping away and back
+    let mut reachable_after_its = Vec::<usize>::new();
+    loop {
+        let its = reachable_after_its.len();
+        let reachable = steps_to
+            .values()
+            .filter(|(steps, _)| *steps <= its && (its - steps).is_multiple_of(2))
+            .count();
+        reachable_after_its.push(reachable);
+        if reachable_after_its.len() > 3 && reachable == reachable_after_its[its - 2] {
+            break;
         }
-        rows.push(row);
     }
-    let rows = rows;
+    reachable_after_its
+}
 
-    let start = || -> (usize, usize) {
-        for (y, row) in rows.iter().enumerate() {
-            for (x, t) in row.iter().enumerate() {
-                if matches!(t, Tile::Start) {
-                    return (x, y);
-                }
-            }
+fn get_reachable_after_steps(n: usize, precalc: &[usize]) -> usize {
+    if let Some(v) = precalc.get(n) {
+        *v
+    } else {
+        let d = n - precalc.len();
+        if d % 2 == 1 {
+            precalc[precalc.len() - 1]
+        } else {
+            precalc[precalc.len() - 2]
         }
-        panic!();
-    }();
-
-    let get_tile_at = |x: usize, y: usize| -> Option<&Tile> { rows.get(y)?.get(x) };
-
-    let calc_reachable_after_its = |start: (usize, usize)| -> Vec<usize> {
-        let mut cur_positions = HashSet::<(usize, usize)>::new();
-        let mut next_positions = HashSet::<(usize, usize)>::new();
-
-        cur_positions.insert(start);
-
-        let mut reachable_after_its = Vec::<usize>::new();
-        reachable_after_its.push(cur_positions.len());
-
-        loop {
-            for (x, y) in cur_positions.drain() {
-                for (nx, ny) in [
-                    (x, y.wrapping_sub(1)),
-                    (x, y + 1),
-                    (x.wrapping_sub(1), y),
-                    (x + 1, y),
-                ] {
-                    if let Some(nt) = get_tile_at(nx, ny) {
-                        if matches!(nt, Tile::Rock) {
-                            continue;
-                        }
-                        next_positions.insert((nx, ny));
-                    }
-                }
-            }
-            cur_positions.extend(next_positions.drain());
-            reachable_after_its.push(cur_positions.len());
-            if reachable_after_its.len() > 3
-                && reachable_after_its[reachable_after_its.len() - 1]
-                    == reachable_after_its[reachable_after_its.len() - 3]
-            {
-                break;
-            }
+    }
+}
+
+/// The plots reachable in exactly `n` steps, found by taking every step, on a map that repeats
+/// forever if `repeats`.
+fn reference_reachable(rows: &Grid<Tile>, n: usize, repeats: bool) -> usize {
+    let (width, height) = (rows.width() as isize, rows.height() as isize);
+    let tile = |p: Pos| {
+        if repeats {
+            let pos = (p.x.rem_euclid(width), p.y.rem_euclid(height));
+            rows.get((pos.0 as usize, pos.1 as usize))
+        } else {
+            p.to_unsigned().and_then(|pos| rows.get(pos))
         }
-        reachable_after_its
     };
 
-    let from_start = calc_reachable_after_its(start);
-
-    let reachable_plots = get_reachable_after_steps(64, &from_start);
-
-    let from_tl = calc_reachable_after_its((0, 0));
-    let from_t = calc_reachable_after_its((start.0, 0));
-    let from_tr = calc_reachable_after_its((rows[0].len() - 1, 0));
-    let from_r = calc_reachable_after_its((rows[0].len() - 1, start.1));
-    let from_br = calc_reachable_after_its((rows[0].len() - 1, rows.len() - 1));
-    let from_b = calc_reachable_after_its((start.0, rows.len() - 1));
-    let from_bl = calc_reachable_after_its((0, rows.len() - 1));
-    let from_l = calc_reachable_after_its((0, start.1));
-
-    fn get_reachable_after_steps(n: usize, precalc: &Vec<usize>) -> usize {
-        if let Some(v) = precalc.get(n) {
-            *v
-        } else {
-            let d = n - precalc.len();
-            if d % 2 == 1 {
-                precalc[precalc.len() - 1]
-            } else {
-                precalc[precalc.len() - 2]
+    let start = Pos::from(find_start(rows));
+    let mut reached = HashSet::from([start]);
+    let mut frontier = vec![start];
+    // Plots first reached after an even and an odd number of steps
+    let mut counts = [1usize, 0];
+    for step in 1..=n {
+        let mut next_frontier = Vec::<Pos>::new();
+        for pos in frontier {
+            for d in Dir::ALL {
+                let next = pos.step(d, 1);
+                if matches!(tile(next), Some(Tile::Plot | Tile::Start)) && reached.insert(next) {
+                    next_frontier.push(next);
+                    counts[step % 2] += 1;
+                }
             }
         }
+        frontier = next_frontier;
     }
+    counts[n % 2]
+}
 
-    let calc_after_steps = |n: usize| -> usize {
-        let centre_only = get_reachable_after_steps(n, &from_start);
-
-        let mut total = centre_only;
-
-        let mut it = 0usize;
-        loop {
-            let steps_to_edges = (rows[0].len() - 1) / 2 + it * rows[0].len();
-            if n > steps_to_edges {
-                let steps_into_edges = n - steps_to_edges - 1;
-                let left = get_reachable_after_steps(steps_into_edges, &from_l);
-                let right = get_reachable_after_steps(steps_into_edges, &from_r);
-                let up = get_reachable_after_steps(steps_into_edges, &from_t);
-                let down = get_reachable_after_steps(steps_into_edges, &from_b);
-
-                total += left + right + up + down;
-                it += 1;
-            } else {
-                break;
-            }
+/// The plots reachable in exactly `n` steps on a map that repeats forever, worked out from
+/// how many are reachable on a single copy of the map from each edge and corner.
+fn reachable_on_repeating_map(rows: &Grid<Tile>, n: usize) -> usize {
+    let (width, height) = (rows.width(), rows.height());
+    let start = find_start(rows);
+    let from_start = calc_reachable_after_its(rows, start);
+    let from_tl = calc_reachable_after_its(rows, (0, 0));
+    let from_t = calc_reachable_after_its(rows, (start.0, 0));
+    let from_tr = calc_reachable_after_its(rows, (width - 1, 0));
+    let from_r = calc_reachable_after_its(rows, (width - 1, start.1));
+    let from_br = calc_reachable_after_its(rows, (width - 1, height - 1));
+    let from_b = calc_reachable_after_its(rows, (start.0, height - 1));
+    let from_bl = calc_reachable_after_its(rows, (0, height - 1));
+    let from_l = calc_reachable_after_its(rows, (0, start.1));
+
+    let centre_only = get_reachable_after_steps(n, &from_start);
+
+    let mut total = centre_only;
+
+    let mut it = 0usize;
+    loop {
+        let steps_to_edges = (width - 1) / 2 + it * width;
+        if n > steps_to_edges {
+            let steps_into_edges = n - steps_to_edges - 1;
+            let left = get_reachable_after_steps(steps_into_edges, &from_l);
+            let right = get_reachable_after_steps(steps_into_edges, &from_r);
+            let up = get_reachable_after_steps(steps_into_edges, &from_t);
+            let down = get_reachable_after_steps(steps_into_edges, &from_b);
+
+            total += left + right + up + down;
+            it += 1;
+        } else {
+            break;
         }
+    }
 
-        let mut it = 0usize;
-        loop {
-            let steps_to_corners = rows[0].len() + it * rows[0].len();
-            if n > steps_to_corners {
-                let steps_into_corners = n - steps_to_corners - 1;
-                let bl = get_reachable_after_steps(steps_into_corners, &from_bl);
-                let br = get_reachable_after_steps(steps_into_corners, &from_br);
-                let tl = get_reachable_after_steps(steps_into_corners, &from_tl);
-                let tr = get_reachable_after_steps(steps_into_corners, &from_tr);
-
-                total += (bl + br + tl + tr) * (it + 1);
-                it += 1;
-            } else {
-                break;
-            }
+    let mut it = 0usize;
+    loop {
+        let steps_to_corners = width + it * width;
+        if n > steps_to_corners {
+            let steps_into_corners = n - steps_to_corners - 1;
+            let bl = get_reachable_after_steps(steps_into_corners, &from_bl);
+            let br = get_reachable_after_steps(steps_into_corners, &from_br);
+            let tl = get_reachable_after_steps(steps_into_corners, &from_tl);
+            let tr = get_reachable_after_steps(steps_into_corners, &from_tr);
+
+            total += (bl + br + tl + tr) * (it + 1);
+            it += 1;
+        } else {
+            break;
         }
+    }
 
-        total
-    };
+    total
+}
 
-    let reachable_plots2 = calc_after_steps(26501365);
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = Grid<Tile>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+        let g = Grid::parse(input, "`.`, `#` or `S`", |c| match c {
+            '.' => Some(Tile::Plot),
+            '#' => Some(Tile::Rock),
+            'S' => Some(Tile::Start),
+            _ => None,
+        })?;
+        g.find_only(input, "`S`", |t| matches!(t, Tile::Start))?;
+        Ok(g)
+    }
 
-    (format!("{reachable_plots}"), format!("{reachable_plots2}"))
+    fn part1(rows: &Grid<Tile>) -> usize {
+        let from_start = calc_reachable_after_its(rows, find_start(rows));
+        get_reachable_after_steps(64, &from_start)
+    }
+
+    fn part2(rows: &Grid<Tile>) -> usize {
+        reachable_on_repeating_map(rows, 26501365)
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let side = size.unwrap_or(131).max(5) | 1;
+        let mid = side / 2;
+        // Like the real input, the start's row and column, the edges and a diamond through the
+        // middle of each edge are kept clear of rocks
+        let mut g = Grid::filled(side, side, '.');
+        for (x, y) in g.positions().collect::<Vec<_>>() {
+            let clear = x == mid
+                || y == mid
+                || x == 0
+                || y == 0
+                || x == side - 1
+                || y == side - 1
+                || (x.abs_diff(mid) + y.abs_diff(mid)).abs_diff(mid) <= 1;
+            if !clear && rng.chance(0.15) {
+                g[(x, y)] = '#';
+            }
+        }
+        g[(mid, mid)] = 'S';
+
+        // Fill in any plots the rocks cut off
+        let reached = search::breadth_first((mid, mid), |&pos| {
+            g.neighbours4(pos)
+                .filter(|&npos| g[npos] != '#')
+                .collect::<Vec<_>>()
+        });
+        let cells = g
+            .iter()
+            .map(|(pos, c)| if reached.contains_key(&pos) { *c } else { '#' })
+            .collect();
+        Grid::new(side, side, cells).to_string()
+    }
+
+    fn cross_check(rows: &Grid<Tile>) -> Vec<Comparison> {
+        let mut comparisons = vec![Comparison::new(
+            "part 1",
+            Self::part1(rows),
+            Some(reference_reachable(rows, 64, false)),
+        )];
+        // Part 2 walks a whole number of maps past the middle of an edge, like these
+        for maps in 1..=4 {
+            let n = (rows.width() - 1) / 2 + maps * rows.width();
+            comparisons.push(Comparison::new(
+                format!("{n} steps on the repeating map"),
+                reachable_on_repeating_map(rows, n),
+                Some(reference_reachable(rows, n, true)),
+            ));
+        }
+        comparisons
+    }
 }
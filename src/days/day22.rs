@@ -1,55 +1,56 @@
 use std::collections::HashSet;
 
+use rayon::prelude::*;
+
+use super::Solution;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::{self, Line, ParseError};
+use crate::trace::{self, Frame};
+
 #[derive(Debug, Clone, Copy)]
-struct End {
-    x: usize,
-    y: usize,
-    z: usize,
+pub struct End {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Block {
-    a: End,
-    b: End,
+pub struct Block {
+    pub a: End,
+    pub b: End,
 }
 
 impl Block {
-    fn dx(&self) -> usize {
+    pub fn dx(&self) -> usize {
         self.b.x - self.a.x + 1
     }
-    fn dy(&self) -> usize {
+    pub fn dy(&self) -> usize {
         self.b.y - self.a.y + 1
     }
-    fn dz(&self) -> usize {
+    pub fn dz(&self) -> usize {
         self.b.z - self.a.z + 1
     }
-    fn x(&self) -> usize {
+    pub fn x(&self) -> usize {
         self.a.x
     }
-    fn y(&self) -> usize {
+    pub fn y(&self) -> usize {
         self.a.y
     }
-    fn z(&self) -> usize {
+    pub fn z(&self) -> usize {
         self.a.z
     }
 
-    fn intersects(&self, other: &Self) -> bool {
-        let ranges_overlap = |a: usize, ad: usize, b: usize, bd: usize| -> bool {
-            if a + ad <= b {
-                return false;
-            } else if b + bd <= a {
-                return false;
-            }
-            return true;
-        };
+    pub fn intersects(&self, other: &Self) -> bool {
+        let ranges_overlap =
+            |a: usize, ad: usize, b: usize, bd: usize| -> bool { a + ad > b && b + bd > a };
         let xo = ranges_overlap(self.x(), self.dx(), other.x(), other.dx());
         let yo = ranges_overlap(self.y(), self.dy(), other.y(), other.dy());
         let zo = ranges_overlap(self.z(), self.dz(), other.z(), other.dz());
-        return xo && yo && zo;
+        xo && yo && zo
     }
 
-    fn lower_by(&self, lz: usize) -> Block {
-        assert!(lz < self.z());
+    pub fn lower_by(&self, lz: usize) -> Block {
         let mut a = self.a;
         let mut b = self.b;
         a.z -= lz;
@@ -58,35 +59,29 @@ impl Block {
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    fn parse_end(s: &str) -> End {
-        let mut it = s.split(",");
-        let mut parse = || it.next().unwrap().parse::<usize>().unwrap();
-        let x = parse();
-        let y = parse();
-        let z = parse();
-        assert!(it.next().is_none());
-        End { x, y, z }
-    }
+fn parse_end(line: &Line, s: &str) -> Result<End, ParseError> {
+    let [x, y, z] = line.tuple::<usize, 3>(s, "a coordinate")?;
+    Ok(End { x, y, z })
+}
 
-    let mut blocks = Vec::<Block>::new();
-    for l in input.lines() {
-        let mut s = l.split("~");
-        let a = parse_end(s.next().unwrap());
-        let b = parse_end(s.next().unwrap());
-        assert!(s.next().is_none());
-        blocks.push(Block { a, b });
+/// The blocks seen from the side, looking along y, with the last one drawn as `@`.
+fn side_view(blocks: &[Block], width: usize, height: usize) -> Grid<char> {
+    let mut view = Grid::filled(width, height, '.');
+    for (i, b) in blocks.iter().enumerate() {
+        let c = if i == blocks.len() - 1 { '@' } else { '#' };
+        for x in b.a.x..=b.b.x {
+            for z in b.a.z..=b.b.z {
+                view[(x, height - z)] = c;
+            }
+        }
     }
+    view
+}
 
-    blocks.sort_by_key(|b| b.z());
-
-    let blocks = blocks;
-
+fn settle(blocks: &[Block]) -> Vec<Block> {
     let mut settled_blocks = Vec::<Block>::new();
 
     for block in blocks.iter() {
-        assert!(blocks.iter().filter(|b| b.intersects(&block)).count() == 1);
-        assert!(settled_blocks.iter().all(|sb| !sb.intersects(&block)));
         let z = block.z();
         let mut nz = block.z();
         while nz > 1 {
@@ -98,98 +93,183 @@ pub fn main(input: &str) -> (String, String) {
             }
         }
         settled_blocks.push(block.lower_by(z - nz));
+
+        trace::record(|palette| {
+            let width = blocks.iter().map(|b| b.b.x + 1).max().unwrap();
+            let height = blocks.iter().map(|b| b.b.z).max().unwrap();
+            Frame::of_grid(
+                format_args!("brick {} fell {}", settled_blocks.len(), z - nz),
+                &side_view(&settled_blocks, width, height),
+                |c| match c {
+                    '@' => palette.highlight,
+                    '#' => palette.feature,
+                    _ => palette.background,
+                },
+            )
+        });
     }
 
-    let mut can_be_removed_count = 0usize;
-    for candidate in settled_blocks.iter() {
-        let mut any_moved = false;
-        for b in settled_blocks.iter() {
-            // Ignore the candidate block.
-            if std::ptr::eq(b, candidate) {
-                continue;
+    settled_blocks
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = Vec<Block>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Block>, ParseError> {
+        let mut lines = Vec::<Line>::new();
+        let mut blocks = Vec::<Block>::new();
+        for line in parse::lines(input) {
+            let (a_text, b_text) = line.split_once(line.text, "~", "`x,y,z~x,y,z`")?;
+            let a = parse_end(&line, a_text)?;
+            let b = parse_end(&line, b_text)?;
+            if a.z == 0 {
+                return Err(line.error_at(a_text, "an end above the ground, at z=1 or more"));
             }
-            // Other block touches the ground, can't be supported by candidate.
-            if b.z() == 1 {
-                continue;
+            if b.x < a.x || b.y < a.y || b.z < a.z {
+                return Err(line.error_at(b_text, format!("an end no lower than `{a_text}`")));
             }
+            let block = Block { a, b };
+            // Settling only moves bricks down into empty space, so they have to start apart
+            if let Some(i) = blocks.iter().position(|other| other.intersects(&block)) {
+                return Err(line.error_at(line.text, format!("a brick clear of line {}", lines[i].num)));
+            }
+            lines.push(line);
+            blocks.push(block);
+        }
 
-            let nb = b.lower_by(1);
+        blocks.sort_by_key(|b| b.z());
 
-            let mut any_intersect = false;
-            for b2 in settled_blocks.iter() {
-                // Don't compare against ourself.
-                if std::ptr::eq(b2, b) {
+        Ok(blocks)
+    }
+
+    fn part1(blocks: &Vec<Block>) -> usize {
+        let settled_blocks = settle(blocks);
+
+        let mut can_be_removed_count = 0usize;
+        for candidate in settled_blocks.iter() {
+            let mut any_moved = false;
+            for b in settled_blocks.iter() {
+                // Ignore the candidate block.
+                if std::ptr::eq(b, candidate) {
                     continue;
                 }
-                // Don't compare against the candidate.
-                if std::ptr::eq(b2, candidate) {
+                // Other block touches the ground, can't be supported by candidate.
+                if b.z() == 1 {
                     continue;
                 }
-                if nb.intersects(b2) {
-                    any_intersect = true;
+
+                let nb = b.lower_by(1);
+
+                let mut any_intersect = false;
+                for b2 in settled_blocks.iter() {
+                    // Don't compare against ourself.
+                    if std::ptr::eq(b2, b) {
+                        continue;
+                    }
+                    // Don't compare against the candidate.
+                    if std::ptr::eq(b2, candidate) {
+                        continue;
+                    }
+                    if nb.intersects(b2) {
+                        any_intersect = true;
+                        break;
+                    }
+                }
+                if !any_intersect {
+                    any_moved = true;
                     break;
                 }
             }
-            if !any_intersect {
-                any_moved = true;
-                break;
+            if !any_moved {
+                can_be_removed_count += 1;
             }
         }
-        if !any_moved {
-            can_be_removed_count += 1;
-        }
+
+        can_be_removed_count
     }
 
-    let mut supported_by_indices = blocks
-        .iter()
-        .map(|_| Vec::<usize>::new())
-        .collect::<Vec<_>>();
+    fn part2(blocks: &Vec<Block>) -> usize {
+        let settled_blocks = settle(blocks);
 
-    // For each block, move it down by one and see which blocks intersect it. Those are supports.
-    for (ib, b) in settled_blocks.iter().enumerate() {
-        if b.z() == 1 {
-            continue;
-        }
-        let nb: Block = b.lower_by(1);
-        for (ib2, b2) in settled_blocks.iter().enumerate() {
-            // Don't compare against ourself.
-            if std::ptr::eq(b2, b) {
+        let mut supported_by_indices = settled_blocks
+            .iter()
+            .map(|_| Vec::<usize>::new())
+            .collect::<Vec<_>>();
+
+        // For each block, move it down by one and see which blocks intersect it. Those are supports.
+        for (ib, b) in settled_blocks.iter().enumerate() {
+            if b.z() == 1 {
                 continue;
             }
-            if nb.intersects(b2) {
-                supported_by_indices[ib].push(ib2);
-            }
-        }
-    }
-
-    let mut causes_to_fall = Vec::<usize>::new();
-
-    for i in 0..settled_blocks.len() {
-        let mut remove_is = HashSet::<usize>::new();
-        remove_is.insert(i);
-        let mut any_added = true;
-        while any_added {
-            any_added = false;
-            for j in 0..settled_blocks.len() {
-                if supported_by_indices[j].is_empty() {
-                    continue;
-                }
-                if remove_is.contains(&j) {
+            let nb: Block = b.lower_by(1);
+            for (ib2, b2) in settled_blocks.iter().enumerate() {
+                // Don't compare against ourself.
+                if std::ptr::eq(b2, b) {
                     continue;
                 }
-                if supported_by_indices[j]
-                    .iter()
-                    .all(|n| remove_is.contains(n))
-                {
-                    remove_is.insert(j);
-                    any_added = true;
+                if nb.intersects(b2) {
+                    supported_by_indices[ib].push(ib2);
                 }
             }
         }
-        causes_to_fall.push(remove_is.len() - 1);
-    }
 
-    let sum_falling = causes_to_fall.iter().sum::<usize>();
+        let causes_to_fall = |i: usize| -> usize {
+            let mut remove_is = HashSet::<usize>::new();
+            remove_is.insert(i);
+            let mut any_added = true;
+            while any_added {
+                any_added = false;
+                for (j, supported_by) in supported_by_indices.iter().enumerate() {
+                    if supported_by.is_empty() {
+                        continue;
+                    }
+                    if remove_is.contains(&j) {
+                        continue;
+                    }
+                    if supported_by.iter().all(|n| remove_is.contains(n)) {
+                        remove_is.insert(j);
+                        any_added = true;
+                    }
+                }
+            }
+            remove_is.len() - 1
+        };
+
+        (0..settled_blocks.len())
+            .into_par_iter()
+            .map(causes_to_fall)
+            .sum()
+    }
 
-    (format!("{can_be_removed_count}"), format!("{sum_falling}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let blocks = size.unwrap_or(1500);
+        let height = blocks / 4 + 10;
+        let mut filled = HashSet::<(usize, usize, usize)>::new();
+        let mut lines = Vec::<String>::new();
+        while lines.len() < blocks {
+            let a = (rng.below(10), rng.below(10), rng.range(1..=height));
+            let len = rng.range(0..=4);
+            let b = match rng.below(3) {
+                0 => (a.0 + len, a.1, a.2),
+                1 => (a.0, a.1 + len, a.2),
+                _ => (a.0, a.1, a.2 + len),
+            };
+            if b.0 >= 10 || b.1 >= 10 {
+                continue;
+            }
+            let cubes = (a.0..=b.0)
+                .flat_map(|x| (a.1..=b.1).flat_map(move |y| (a.2..=b.2).map(move |z| (x, y, z))))
+                .collect::<Vec<_>>();
+            if cubes.iter().any(|c| filled.contains(c)) {
+                continue;
+            }
+            filled.extend(cubes);
+            lines.push(format!("{},{},{}~{},{},{}\n", a.0, a.1, a.2, b.0, b.1, b.2));
+        }
+        lines.concat()
+    }
 }
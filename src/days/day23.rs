@@ -1,272 +1,286 @@
 use std::collections::HashMap;
 
+use super::Solution;
+use crate::dir::Dir;
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
+
 #[derive(Debug, Clone, Copy)]
-enum Tile {
+pub enum Tile {
     Path,
     Forest,
-    SlopeUp,
-    SlopeDown,
-    SlopeLeft,
-    SlopeRight,
+    Slope(Dir),
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut map = Vec::<Vec<Tile>>::new();
-    for l in input.lines() {
-        let mut row = Vec::<Tile>::new();
-        for c in l.chars() {
-            let t = match c {
-                '#' => Tile::Forest,
-                '.' => Tile::Path,
-                '^' => Tile::SlopeUp,
-                'v' => Tile::SlopeDown,
-                '<' => Tile::SlopeLeft,
-                '>' => Tile::SlopeRight,
-                other => panic!("{other}"),
-            };
-            row.push(t);
-        }
-        map.push(row);
+fn find_start_end(map: &Grid<Tile>) -> ((usize, usize), (usize, usize)) {
+    let path_in_row = |y: usize| -> (usize, usize) {
+        let x = map.row(y).iter().position(|t| matches!(t, Tile::Path));
+        (x.unwrap(), y)
+    };
+    (path_in_row(0), path_in_row(map.height() - 1))
+}
+
+#[derive(Debug, Clone)]
+struct Head {
+    pos: (usize, usize),
+    visited: HashMap<(usize, usize), usize>,
+}
+
+impl Head {
+    fn move_to(&mut self, pos: (usize, usize)) {
+        assert!(!self.visited.contains_key(&pos));
+        self.visited.insert(self.pos, self.visited.len());
+        self.pos = pos;
     }
-    let map = map;
 
-    let start = || -> (usize, usize) {
-        for (i, t) in map[0].iter().enumerate() {
-            if matches!(t, Tile::Path) {
-                return (i, 0);
-            }
-        }
-        panic!();
-    }();
+    fn possible_moves(&self, map: &Grid<Tile>, ignore_slopes: bool) -> [Option<(usize, usize)>; 4] {
+        let mut ret = [None::<(usize, usize)>; 4];
+        let mut idx = 0usize;
+        let mut push = |pos: (usize, usize)| {
+            ret[idx] = Some(pos);
+            idx += 1;
+        };
 
-    let end = || -> (usize, usize) {
-        for (i, t) in map.last().unwrap().iter().enumerate() {
-            if matches!(t, Tile::Path) {
-                return (i, map.len() - 1);
+        let t = &map[self.pos];
+        let t = if ignore_slopes {
+            match t {
+                Tile::Path => Tile::Path,
+                Tile::Forest => Tile::Forest,
+                Tile::Slope(_) => Tile::Path,
+            }
+        } else {
+            *t
+        };
+        let next_positions = match t {
+            Tile::Path => Dir::ALL.map(|d| map.step(self.pos, d)),
+            Tile::Forest => panic!(),
+            Tile::Slope(d) => [map.step(self.pos, d), None, None, None],
+        };
+        for npos in next_positions.into_iter().flatten() {
+            if self.visited.contains_key(&npos) {
+                continue;
+            }
+            if !matches!(map[npos], Tile::Forest) {
+                push(npos);
             }
         }
-        panic!();
-    }();
 
-    #[derive(Debug, Clone)]
-    struct Head {
-        pos: (usize, usize),
-        visited: HashMap<(usize, usize), usize>,
+        ret
     }
+}
 
-    impl Head {
-        fn move_to(&mut self, pos: (usize, usize)) {
-            assert!(!self.visited.contains_key(&pos));
-            self.visited.insert(self.pos, self.visited.len());
-            self.pos = pos;
-        }
+// Convert the map into a graph
+#[derive(Debug)]
+struct Node {
+    connects_to: Vec<(usize, usize)>,
+}
 
-        fn possible_moves(
-            &self,
-            map: &Vec<Vec<Tile>>,
-            ignore_slopes: bool,
-        ) -> [Option<(usize, usize)>; 4] {
-            let mut ret = [None::<(usize, usize)>; 4];
-            let mut idx = 0usize;
-            let mut push = |pos: (usize, usize)| {
-                ret[idx] = Some(pos);
-                idx += 1;
-            };
+fn calc_graph(map: &Grid<Tile>, ignore_slopes: bool) -> Vec<Node> {
+    let (start, end) = find_start_end(map);
+    let mut pending_nodes = vec![start, end];
+    let mut next_pending_nodes = Vec::<(usize, usize)>::new();
 
-            let at = |pos: (usize, usize)| -> Option<&Tile> { map.get(pos.1)?.get(pos.0) };
+    let mut processed_nodes = HashMap::<(usize, usize), usize>::new();
 
-            let (x, y) = self.pos;
-            let t = at(self.pos).unwrap();
-            let t = if ignore_slopes {
-                match t {
-                    Tile::Path => Tile::Path,
-                    Tile::Forest => Tile::Forest,
-                    Tile::SlopeUp => Tile::Path,
-                    Tile::SlopeDown => Tile::Path,
-                    Tile::SlopeLeft => Tile::Path,
-                    Tile::SlopeRight => Tile::Path,
-                }
-            } else {
-                *t
-            };
-            let up = (x, y.wrapping_sub(1));
-            let down = (x, y + 1);
-            let left = (x.wrapping_sub(1), y);
-            let right = (x + 1, y);
-            let next_positions = match t {
-                Tile::Path => [Some(up), Some(down), Some(left), Some(right)],
-                Tile::Forest => panic!(),
-                Tile::SlopeUp => [Some(up), None, None, None],
-                Tile::SlopeDown => [Some(down), None, None, None],
-                Tile::SlopeLeft => [Some(left), None, None, None],
-                Tile::SlopeRight => [Some(right), None, None, None],
-            };
-            for npos in next_positions {
-                if let Some(npos) = npos {
-                    if self.visited.contains_key(&npos) {
-                        continue;
-                    }
-                    if let Some(t) = at(npos) {
-                        if !matches!(t, Tile::Forest) {
-                            push(npos);
+    struct TempNode {
+        connects_to: Vec<((usize, usize), usize)>,
+    }
+
+    let mut temp_nodes = Vec::<TempNode>::new();
+
+    while !pending_nodes.is_empty() {
+        for pn in pending_nodes.drain(..) {
+            if processed_nodes.contains_key(&pn) {
+                continue;
+            }
+            // Walk until we find the next node that has more than one option
+            let mut heads = Vec::<Head>::new();
+            let mut pmoves = Vec::<(usize, usize)>::new();
+            let mut connects_to = Vec::<((usize, usize), usize)>::new();
+
+            heads.push(Head {
+                pos: pn,
+                visited: HashMap::new(),
+            });
+            let mut next_heads = Vec::<Head>::new();
+            while !heads.is_empty() {
+                for head in heads.drain(..) {
+                    let possible_moves = head.possible_moves(map, ignore_slopes);
+                    pmoves.clear();
+                    pmoves.extend(possible_moves.into_iter().flatten());
+                    if pmoves.len() > 1 && head.pos != pn
+                        || pmoves.is_empty() && (head.pos == start || head.pos == end)
+                    {
+                        connects_to.push((head.pos, head.visited.len()));
+                    } else {
+                        for pmove in pmoves.iter() {
+                            let mut h = head.clone();
+                            h.move_to(*pmove);
+                            next_heads.push(h);
                         }
                     }
                 }
+                heads.append(&mut next_heads);
             }
-
-            ret
+            next_pending_nodes.extend(connects_to.iter().map(|(k, _)| k));
+            processed_nodes.insert(pn, processed_nodes.len());
+            temp_nodes.push(TempNode { connects_to });
         }
+        pending_nodes.append(&mut next_pending_nodes);
     }
 
-    // Convert the map into a graph
-    #[derive(Debug)]
-    struct Node {
-        connects_to: Vec<(usize, usize)>,
+    let mut nodes = Vec::<Node>::new();
+    for temp_node in temp_nodes {
+        let connects_to = temp_node
+            .connects_to
+            .iter()
+            .map(|(pos, dist)| (processed_nodes[pos], *dist))
+            .collect::<Vec<_>>();
+        nodes.push(Node { connects_to })
     }
+    nodes
+}
 
-    let calc_graph = |ignore_slopes: bool| -> Vec<Node> {
-        let mut pending_nodes = vec![start, end];
-        let mut next_pending_nodes = Vec::<(usize, usize)>::new();
+fn calc_max_path(graph: &[Node]) -> usize {
+    let start_idx = 0;
+    let end_idx = 1;
 
-        let mut processed_nodes = HashMap::<(usize, usize), usize>::new();
+    assert!(graph.len() < u64::BITS as usize);
+    #[derive(Debug, Clone)]
+    struct Path {
+        idx: usize,
+        visited: usize,
+        distance: usize,
+    }
 
-        struct TempNode {
-            connects_to: Vec<((usize, usize), usize)>,
+    impl Path {
+        fn move_to(&mut self, dst: usize, dist: usize) {
+            assert!(!self.visited(dst));
+            self.visited |= 1 << dst;
+            self.idx = dst;
+            self.distance += dist;
         }
 
-        let mut temp_nodes = Vec::<TempNode>::new();
-
-        while !pending_nodes.is_empty() {
-            for pn in pending_nodes.drain(..) {
-                if processed_nodes.contains_key(&pn) {
-                    continue;
-                }
-                // Walk until we find the next node that has more than one option
-                let mut heads = Vec::<Head>::new();
-                let mut pmoves = Vec::<(usize, usize)>::new();
-                let mut connects_to = Vec::<((usize, usize), usize)>::new();
+        fn visited(&self, dst: usize) -> bool {
+            (self.visited & 1 << dst) != 0
+        }
+    }
 
-                heads.push(Head {
-                    pos: pn,
-                    visited: HashMap::new(),
-                });
-                let mut next_heads = Vec::<Head>::new();
-                while !heads.is_empty() {
-                    for head in heads.drain(..) {
-                        let possible_moves = head.possible_moves(&map, ignore_slopes);
-                        pmoves.clear();
-                        for pmove in possible_moves {
-                            if let Some(pmove) = pmove {
-                                pmoves.push(pmove);
-                            }
-                        }
-                        if pmoves.len() > 1 && head.pos != pn
-                            || pmoves.len() == 0 && (head.pos == start || head.pos == end)
-                        {
-                            connects_to.push((head.pos, head.visited.len()));
-                        } else {
-                            for pmove in pmoves.iter() {
-                                let mut h = head.clone();
-                                h.move_to(*pmove);
-                                next_heads.push(h);
+    let mut paths = vec![Path {
+        idx: start_idx,
+        visited: 0,
+        distance: 0,
+    }];
+    let mut next_paths = Vec::<Path>::new();
+    let mut max_completed = None::<Path>;
+    let mut num_completed = 0usize;
+    let mut num_terminated = 0usize;
+    while !paths.is_empty() {
+        let mut process_path = |path: Path| {
+            let node = &graph[path.idx];
+            for (dst, dist) in node.connects_to.iter() {
+                if !path.visited(*dst) {
+                    let mut npath = path.clone();
+                    npath.move_to(*dst, *dist);
+                    if *dst == end_idx {
+                        num_completed += 1;
+                        max_completed = match max_completed.take() {
+                            Some(v) => {
+                                if npath.distance > v.distance {
+                                    Some(npath)
+                                } else {
+                                    Some(v)
+                                }
                             }
-                        }
+                            None => Some(npath),
+                        };
+                    } else {
+                        next_paths.push(npath);
                     }
-                    heads.extend(next_heads.drain(..));
+                } else {
+                    num_terminated += 1;
                 }
-                next_pending_nodes.extend(connects_to.iter().map(|(k, _)| k));
-                processed_nodes.insert(pn, processed_nodes.len());
-                temp_nodes.push(TempNode { connects_to });
             }
-            pending_nodes.extend(next_pending_nodes.drain(..));
-        }
+        };
+        process_path(paths.pop().unwrap());
+        paths.append(&mut next_paths);
+    }
 
-        let mut nodes = Vec::<Node>::new();
-        for temp_node in temp_nodes {
-            let connects_to = temp_node
-                .connects_to
-                .iter()
-                .map(|(pos, dist)| (processed_nodes[pos], *dist))
-                .collect::<Vec<_>>();
-            nodes.push(Node { connects_to })
-        }
-        nodes
-    };
+    max_completed.unwrap().distance
+}
 
-    let start_idx = 0;
-    let end_idx = 1;
+pub struct Day23;
 
-    let first_graph = calc_graph(false);
-    let second_graph = calc_graph(true);
+impl Solution for Day23 {
+    type Input = Grid<Tile>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    let calc_max_path = |graph: &Vec<Node>| -> usize {
-        assert!(graph.len() < u64::BITS as usize);
-        #[derive(Debug, Clone)]
-        struct Path {
-            idx: usize,
-            visited: usize,
-            distance: usize,
+    fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+        let map = Grid::parse(input, "`#`, `.` or a slope", |c| match c {
+            '#' => Some(Tile::Forest),
+            '.' => Some(Tile::Path),
+            c => Dir::from_arrow(c).map(Tile::Slope),
+        })?
+        .non_empty(input)?;
+        // The walk starts and ends on the paths through the top and bottom rows
+        for (y, which) in [(0, "top"), (map.height() - 1, "bottom")] {
+            if !map.row(y).iter().any(|t| matches!(t, Tile::Path)) {
+                let line = parse::lines(input).nth(y).unwrap();
+                return Err(line.error_at(line.text, format!("a `.` in the {which} row")));
+            }
         }
+        Ok(map)
+    }
 
-        impl Path {
-            fn move_to(&mut self, dst: usize, dist: usize) {
-                assert!(!self.visited(dst));
-                self.visited |= 1 << dst;
-                self.idx = dst;
-                self.distance += dist;
-            }
+    fn part1(map: &Grid<Tile>) -> usize {
+        calc_max_path(&calc_graph(map, false))
+    }
 
-            fn visited(&self, dst: usize) -> bool {
-                (self.visited & 1 << dst) != 0
-            }
+    fn part2(map: &Grid<Tile>) -> usize {
+        calc_max_path(&calc_graph(map, true))
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        // A square of junctions joined to their neighbours by straight paths, with slopes at both
+        // ends of each path so part 1 can only head right or down, like the real input. With 8 a
+        // side the graph's nodes don't fit in calc_max_path's bitset, and with 7 its search takes
+        // minutes.
+        let junctions = size.unwrap_or(6).clamp(1, 6);
+        let mut xs = vec![1usize];
+        let mut ys = vec![rng.range(2..=12)];
+        for _ in 1..junctions {
+            xs.push(xs.last().unwrap() + rng.range(3..=24));
+            ys.push(ys.last().unwrap() + rng.range(3..=24));
         }
+        let (last_x, last_y) = (xs[junctions - 1], ys[junctions - 1]);
+        let (width, height) = (last_x + 2, last_y + rng.range(2..=12) + 1);
 
-        let mut paths = Vec::<Path>::new();
-        paths.push(Path {
-            idx: start_idx,
-            visited: 0,
-            distance: 0,
-        });
-        let mut next_paths = Vec::<Path>::new();
-        let mut max_completed = None::<Path>;
-        let mut num_completed = 0usize;
-        let mut num_terminated = 0usize;
-        while !paths.is_empty() {
-            let mut process_path = |path: Path| {
-                let node = &graph[path.idx];
-                for (dst, dist) in node.connects_to.iter() {
-                    if !path.visited(*dst) {
-                        let mut npath = path.clone();
-                        npath.move_to(*dst, *dist);
-                        if *dst == end_idx {
-                            num_completed += 1;
-                            max_completed = match max_completed.take() {
-                                Some(v) => {
-                                    if npath.distance > v.distance {
-                                        Some(npath)
-                                    } else {
-                                        Some(v)
-                                    }
-                                }
-                                None => Some(npath),
-                            };
-                        } else {
-                            next_paths.push(npath);
-                        }
-                    } else {
-                        num_terminated += 1;
+        let mut g = Grid::filled(width, height, '#');
+        for y in 0..ys[0] {
+            g[(xs[0], y)] = '.';
+        }
+        for y in last_y..height {
+            g[(last_x, y)] = '.';
+        }
+        for (j, &y) in ys.iter().enumerate() {
+            for (i, &x) in xs.iter().enumerate() {
+                g[(x, y)] = '.';
+                if let Some(&next_x) = xs.get(i + 1) {
+                    for px in x + 1..next_x {
+                        let slope = px == x + 1 || px == next_x - 1;
+                        g[(px, y)] = if slope { '>' } else { '.' };
                     }
                 }
-            };
-            process_path(paths.pop().unwrap());
-            paths.extend(next_paths.drain(..));
+                if let Some(&next_y) = ys.get(j + 1) {
+                    for py in y + 1..next_y {
+                        let slope = py == y + 1 || py == next_y - 1;
+                        g[(x, py)] = if slope { 'v' } else { '.' };
+                    }
+                }
+            }
         }
-
-        max_completed.unwrap().distance
-    };
-
-    let max_first = calc_max_path(&first_graph);
-    let max_second = calc_max_path(&second_graph);
-
-    (format!("{max_first}"), format!("{max_second}"))
+        g.to_string()
+    }
 }
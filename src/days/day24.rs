@@ -1,118 +1,156 @@
 use nalgebra::Vector3;
-use regex::Regex;
 use z3::{ast::Ast, Config, Context};
 
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+
 #[derive(Debug)]
-struct HailStone {
-    p: Vector3<i64>,
-    d: Vector3<i64>,
+pub struct HailStone {
+    pub p: Vector3<i64>,
+    pub d: Vector3<i64>,
 }
 
 impl HailStone {
-    fn at_t(&self, t: i64) -> Vector3<i64> {
+    pub fn at_t(&self, t: i64) -> Vector3<i64> {
         self.p + self.d * t
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let re = Regex::new(r"(\d+),\s+(\d+),\s+(\d+)\s+@\s+(-?\d+),\s+(-?\d+),\s+(-?\d+)").unwrap();
-
-    let mut hailstones = Vec::<HailStone>::new();
-    for l in input.lines() {
-        let c = re.captures(l).unwrap();
-        let parse = |i: usize| -> i64 { c.get(i).unwrap().as_str().parse::<i64>().unwrap() };
-        let h = HailStone {
-            p: Vector3::<i64>::new(parse(1), parse(2), parse(3)),
-            d: Vector3::<i64>::new(parse(4), parse(5), parse(6)),
-        };
-        hailstones.push(h);
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Vec<HailStone>;
+    type Answer1 = usize;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Vec<HailStone>, ParseError> {
+        let mut hailstones = Vec::<HailStone>::new();
+        for line in parse::lines(input) {
+            let (p, d) = line.split_once(line.text, "@", "`px, py, pz @ vx, vy, vz`")?;
+            let h = HailStone {
+                p: Vector3::<i64>::from(line.tuple::<i64, 3>(p, "a number")?),
+                d: Vector3::<i64>::from(line.tuple::<i64, 3>(d, "a number")?),
+            };
+            hailstones.push(h);
+        }
+
+        Ok(hailstones)
     }
 
-    let min_x = 200000000000000.0;
-    let max_x = 400000000000000.0;
-    let min_y = 200000000000000.0;
-    let max_y = 400000000000000.0;
+    fn part1(hailstones: &Vec<HailStone>) -> usize {
+        let min_x = 200000000000000.0;
+        let max_x = 400000000000000.0;
+        let min_y = 200000000000000.0;
+        let max_y = 400000000000000.0;
 
-    let mut xy_col_in_range = 0usize;
+        let mut xy_col_in_range = 0usize;
 
-    for (i, hs) in hailstones.iter().enumerate() {
-        for hs2 in hailstones[i + 1..].iter() {
-            let p1 = hs.at_t(0);
-            let p2 = hs.at_t(1);
-            let p3 = hs2.at_t(0);
-            let p4 = hs2.at_t(1);
+        for (i, hs) in hailstones.iter().enumerate() {
+            for hs2 in hailstones[i + 1..].iter() {
+                let p1 = hs.at_t(0);
+                let p2 = hs.at_t(1);
+                let p3 = hs2.at_t(0);
+                let p4 = hs2.at_t(1);
 
-            let ua_num = (p4.x - p3.x) * (p1.y - p3.y) - (p4.y - p3.y) * (p1.x - p3.x);
+                let ua_num = (p4.x - p3.x) * (p1.y - p3.y) - (p4.y - p3.y) * (p1.x - p3.x);
 
-            let ub_num = (p2.x - p1.x) * (p1.y - p3.y) - (p2.y - p1.y) * (p1.x - p3.x);
-            let denom = (p4.y - p3.y) * (p2.x - p1.x) - (p4.x - p3.x) * (p2.y - p1.y);
+                let ub_num = (p2.x - p1.x) * (p1.y - p3.y) - (p2.y - p1.y) * (p1.x - p3.x);
+                let denom = (p4.y - p3.y) * (p2.x - p1.x) - (p4.x - p3.x) * (p2.y - p1.y);
 
-            if denom == 0 {
-                continue;
-            }
+                if denom == 0 {
+                    continue;
+                }
 
-            let ua = ua_num as f64 / denom as f64;
-            let ub = ub_num as f64 / denom as f64;
+                let ua = ua_num as f64 / denom as f64;
+                let ub = ub_num as f64 / denom as f64;
 
-            if ua < 0.0 {
-                continue;
-            }
+                if ua < 0.0 {
+                    continue;
+                }
 
-            if ub < 0.0 {
-                continue;
-            }
+                if ub < 0.0 {
+                    continue;
+                }
 
-            let x = p1.x as f64 + ua * (p2.x - p1.x) as f64;
-            let y = p1.y as f64 + ua * (p2.y - p1.y) as f64;
+                let x = p1.x as f64 + ua * (p2.x - p1.x) as f64;
+                let y = p1.y as f64 + ua * (p2.y - p1.y) as f64;
 
-            if min_x > x || max_x < x {
-                continue;
-            }
+                if min_x > x || max_x < x {
+                    continue;
+                }
 
-            if min_y > y || max_y < y {
-                continue;
-            }
+                if min_y > y || max_y < y {
+                    continue;
+                }
 
-            xy_col_in_range += 1;
+                xy_col_in_range += 1;
+            }
         }
-    }
 
-    let cfg = Config::new();
-    let ctx = Context::new(&cfg);
-    let solver = z3::Solver::new(&ctx);
-
-    let px = z3::ast::Int::new_const(&ctx, "px");
-    let py = z3::ast::Int::new_const(&ctx, "py");
-    let pz = z3::ast::Int::new_const(&ctx, "pz");
-    let dx = z3::ast::Int::new_const(&ctx, "dx");
-    let dy = z3::ast::Int::new_const(&ctx, "dy");
-    let dz = z3::ast::Int::new_const(&ctx, "dz");
-
-    for hs in hailstones.iter() {
-        let hpx = z3::ast::Int::from_i64(&ctx, hs.p.x);
-        let hpy = z3::ast::Int::from_i64(&ctx, hs.p.y);
-        let hpz = z3::ast::Int::from_i64(&ctx, hs.p.z);
-        let hdx = z3::ast::Int::from_i64(&ctx, hs.d.x);
-        let hdy = z3::ast::Int::from_i64(&ctx, hs.d.y);
-        let hdz = z3::ast::Int::from_i64(&ctx, hs.d.z);
-        let t = z3::ast::Int::fresh_const(&ctx, "t");
-
-        solver.assert(&(&hpx + &t * &hdx)._eq(&(&px + &t * &dx)));
-        solver.assert(&(&hpy + &t * &hdy)._eq(&(&py + &t * &dy)));
-        solver.assert(&(&hpz + &t * &hdz)._eq(&(&pz + &t * &dz)));
+        xy_col_in_range
     }
 
-    let pos_sum = match solver.check() {
-        z3::SatResult::Unsat => panic!(),
-        z3::SatResult::Unknown => panic!(),
-        z3::SatResult::Sat => {
-            let model = solver.get_model().unwrap();
-            let px = model.get_const_interp(&px).unwrap();
-            let py = model.get_const_interp(&py).unwrap();
-            let pz = model.get_const_interp(&pz).unwrap();
-            px.as_i64().unwrap() + py.as_i64().unwrap() + pz.as_i64().unwrap()
+    fn part2(hailstones: &Vec<HailStone>) -> i64 {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = z3::Solver::new(&ctx);
+
+        let px = z3::ast::Int::new_const(&ctx, "px");
+        let py = z3::ast::Int::new_const(&ctx, "py");
+        let pz = z3::ast::Int::new_const(&ctx, "pz");
+        let dx = z3::ast::Int::new_const(&ctx, "dx");
+        let dy = z3::ast::Int::new_const(&ctx, "dy");
+        let dz = z3::ast::Int::new_const(&ctx, "dz");
+
+        for hs in hailstones.iter() {
+            let hpx = z3::ast::Int::from_i64(&ctx, hs.p.x);
+            let hpy = z3::ast::Int::from_i64(&ctx, hs.p.y);
+            let hpz = z3::ast::Int::from_i64(&ctx, hs.p.z);
+            let hdx = z3::ast::Int::from_i64(&ctx, hs.d.x);
+            let hdy = z3::ast::Int::from_i64(&ctx, hs.d.y);
+            let hdz = z3::ast::Int::from_i64(&ctx, hs.d.z);
+            let t = z3::ast::Int::fresh_const(&ctx, "t");
+
+            solver.assert(&(&hpx + &t * &hdx)._eq(&(&px + &t * &dx)));
+            solver.assert(&(&hpy + &t * &hdy)._eq(&(&py + &t * &dy)));
+            solver.assert(&(&hpz + &t * &hdz)._eq(&(&pz + &t * &dz)));
+        }
+
+        match solver.check() {
+            z3::SatResult::Unsat => panic!(),
+            z3::SatResult::Unknown => panic!(),
+            z3::SatResult::Sat => {
+                let model = solver.get_model().unwrap();
+                let px = model.get_const_interp(&px).unwrap();
+                let py = model.get_const_interp(&py).unwrap();
+                let pz = model.get_const_interp(&pz).unwrap();
+                px.as_i64().unwrap() + py.as_i64().unwrap() + pz.as_i64().unwrap()
+            }
         }
-    };
+    }
 
-    (format!("{xy_col_in_range}"), format!("{pos_sum}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        // Every hailstone is on course to meet a rock thrown from `p` at `v`, each at its own time
+        let area = 200_000_000_000_000..=400_000_000_000_000;
+        let p = [0; 3].map(|_| rng.range_i64(area.clone()));
+        let v = [0; 3].map(|_| rng.range_i64(-300..=300));
+        let mut out = String::new();
+        // Part 2 is only quick to solve with a few hailstones to pin the rock down
+        for _ in 0..size.unwrap_or(300).max(4) {
+            let t = rng.range_i64(100_000_000_000..=1_000_000_000_000);
+            let hv = loop {
+                let hv = [0; 3].map(|_| rng.range_i64(-300..=300));
+                if hv != v {
+                    break hv;
+                }
+            };
+            let hp = [0, 1, 2].map(|i| p[i] + (v[i] - hv[i]) * t);
+            out += &format!(
+                "{}, {}, {} @ {}, {}, {}\n",
+                hp[0], hp[1], hp[2], hv[0], hv[1], hv[2]
+            );
+        }
+        out
+    }
 }
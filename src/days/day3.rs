@@ -1,83 +1,106 @@
 use std::collections::BTreeSet;
 
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, Line, ParseError};
+
 #[derive(Debug, Ord, PartialOrd, PartialEq, Eq, Copy, Clone)]
-struct PartNum {
-    y: usize,
-    x: usize,
-    val: usize,
+pub struct PartNum {
+    pub y: usize,
+    pub x: usize,
+    pub val: usize,
 }
 
 #[derive(Debug)]
-enum Entry {
-    Number(char, PartNum),
+pub enum Entry {
+    Number(PartNum),
     Symbol(char),
     Nothing,
 }
 
 #[derive(Debug)]
-struct Row {
-    entries: Vec<Entry>,
+pub struct Row {
+    pub entries: Vec<Entry>,
 }
 
 #[derive(Debug)]
-struct Schematic {
-    rows: Vec<Row>,
+pub struct Schematic {
+    pub rows: Vec<Row>,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut rows = Vec::<Row>::new();
-
-    for (row_idx, l) in input.lines().enumerate() {
-        let mut entries = Vec::<Entry>::new();
-        let mut numbuf: String = String::new();
-        for c in l.chars() {
-            let mut was_num = false;
-            match c {
-                '.' => {
-                    entries.push(Entry::Nothing);
-                }
-                c if c.is_numeric() => {
-                    was_num = true;
-                    numbuf.push(c);
+fn parse_part_num(line: &Line, col: usize, digits: &str) -> Result<usize, ParseError> {
+    digits
+        .parse::<usize>()
+        .map_err(|_| line.error(col + 1, "a part number", format!("`{digits}`")))
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Schematic;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Schematic, ParseError> {
+        let mut rows = Vec::<Row>::new();
+
+        for line in parse::lines(input) {
+            let row_idx = line.num - 1;
+            let mut entries = Vec::<Entry>::new();
+            let mut numbuf: String = String::new();
+            let mut num_col = 0usize;
+            for (col, c) in line.text.chars().enumerate() {
+                let mut was_num = false;
+                match c {
+                    '.' => {
+                        entries.push(Entry::Nothing);
+                    }
+                    c if c.is_ascii_digit() => {
+                        was_num = true;
+                        if numbuf.is_empty() {
+                            num_col = col;
+                        }
+                        numbuf.push(c);
+                    }
+                    other => entries.push(Entry::Symbol(other)),
                 }
-                other => entries.push(Entry::Symbol(other)),
-            }
-            if !was_num {
-                if !numbuf.is_empty() {
-                    let val = numbuf.parse::<usize>().unwrap();
+                if !was_num && !numbuf.is_empty() {
+                    let val = parse_part_num(&line, num_col, &numbuf)?;
                     let prev = entries.pop().unwrap();
                     let x = entries.len();
 
-                    for c in numbuf.chars() {
-                        entries.push(Entry::Number(c, PartNum { x, y: row_idx, val }));
+                    for _ in numbuf.chars() {
+                        entries.push(Entry::Number(PartNum { x, y: row_idx, val }));
                     }
                     entries.push(prev);
                     numbuf.clear();
                 }
             }
-        }
-        if !numbuf.is_empty() {
-            let val = numbuf.parse::<usize>().unwrap();
-            let x = entries.len();
-            for c in numbuf.chars() {
-                entries.push(Entry::Number(c, PartNum { x, y: row_idx, val }));
+            if !numbuf.is_empty() {
+                let val = parse_part_num(&line, num_col, &numbuf)?;
+                let x = entries.len();
+                for _ in numbuf.chars() {
+                    entries.push(Entry::Number(PartNum { x, y: row_idx, val }));
+                }
             }
-        }
 
-        rows.push(Row { entries });
+            rows.push(Row { entries });
+        }
+        Ok(Schematic { rows })
     }
-    let s = Schematic { rows };
-
-    let mut part_numbers = BTreeSet::<PartNum>::new();
-    for (row_idx, row) in s.rows.iter().enumerate() {
-        for (col_idx, entry) in row.entries.iter().enumerate() {
-            if let Entry::Number(_, v) = entry {
-                for x in col_idx.saturating_sub(1)..=col_idx.saturating_add(1) {
-                    for y in row_idx.saturating_sub(1)..=row_idx.saturating_add(1) {
-                        if let Some(other_row) = s.rows.get(y) {
-                            if let Some(other_entry) = other_row.entries.get(x) {
-                                if matches!(other_entry, Entry::Symbol(_)) {
-                                    part_numbers.insert(*v);
+
+    fn part1(s: &Schematic) -> usize {
+        let mut part_numbers = BTreeSet::<PartNum>::new();
+        for (row_idx, row) in s.rows.iter().enumerate() {
+            for (col_idx, entry) in row.entries.iter().enumerate() {
+                if let Entry::Number(v) = entry {
+                    for x in col_idx.saturating_sub(1)..=col_idx.saturating_add(1) {
+                        for y in row_idx.saturating_sub(1)..=row_idx.saturating_add(1) {
+                            if let Some(other_row) = s.rows.get(y) {
+                                if let Some(other_entry) = other_row.entries.get(x) {
+                                    if matches!(other_entry, Entry::Symbol(_)) {
+                                        part_numbers.insert(*v);
+                                    }
                                 }
                             }
                         }
@@ -85,35 +108,60 @@ pub fn main(input: &str) -> (String, String) {
                 }
             }
         }
+        part_numbers.iter().map(|x| -> usize { x.val }).sum()
     }
-    let part_nums_summed: usize = part_numbers.iter().map(|x| -> usize { x.val }).sum();
-
-    let mut summed_ratio = 0usize;
-    for (row_idx, row) in s.rows.iter().enumerate() {
-        for (col_idx, entry) in row.entries.iter().enumerate() {
-            if let Entry::Symbol('*') = entry {
-                let mut part_numbers = BTreeSet::<PartNum>::new();
-                for x in col_idx.saturating_sub(1)..=col_idx.saturating_add(1) {
-                    for y in row_idx.saturating_sub(1)..=row_idx.saturating_add(1) {
-                        if let Some(other_row) = s.rows.get(y) {
-                            if let Some(other_entry) = other_row.entries.get(x) {
-                                if let Entry::Number(_, v) = other_entry {
+
+    fn part2(s: &Schematic) -> usize {
+        let mut summed_ratio = 0usize;
+        for (row_idx, row) in s.rows.iter().enumerate() {
+            for (col_idx, entry) in row.entries.iter().enumerate() {
+                if let Entry::Symbol('*') = entry {
+                    let mut part_numbers = BTreeSet::<PartNum>::new();
+                    for x in col_idx.saturating_sub(1)..=col_idx.saturating_add(1) {
+                        for y in row_idx.saturating_sub(1)..=row_idx.saturating_add(1) {
+                            if let Some(other_row) = s.rows.get(y) {
+                                if let Some(Entry::Number(v)) = other_row.entries.get(x) {
                                     part_numbers.insert(*v);
                                 }
                             }
                         }
                     }
-                }
 
-                if part_numbers.len() == 2 {
-                    let a = part_numbers.first().unwrap().val;
-                    let b = part_numbers.last().unwrap().val;
-                    let ratio = a * b;
-                    summed_ratio += ratio;
+                    if part_numbers.len() == 2 {
+                        let a = part_numbers.first().unwrap().val;
+                        let b = part_numbers.last().unwrap().val;
+                        let ratio = a * b;
+                        summed_ratio += ratio;
+                    }
                 }
             }
         }
+
+        summed_ratio
     }
 
-    (format!("{part_nums_summed}"), format!("{summed_ratio}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let side = size.unwrap_or(140);
+        let mut out = String::new();
+        for _ in 0..side {
+            let mut row = String::new();
+            while row.len() < side {
+                match rng.below(10) {
+                    0 => row.push(*rng.pick(b"*#+$/=%@&-") as char),
+                    1 | 2 => {
+                        // Always followed by a `.`, so it doesn't run into the next number
+                        let num = rng.range(1..=999).to_string();
+                        if num.len() < side - row.len() {
+                            row += &num;
+                        }
+                        row.push('.');
+                    }
+                    _ => row.push('.'),
+                }
+            }
+            out += &row;
+            out.push('\n');
+        }
+        out
+    }
 }
@@ -1,12 +1,14 @@
-use regex::Regex;
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
 
-struct Card {
-    card_id: usize,
-    matches: usize,
+pub struct Card {
+    pub card_id: usize,
+    pub matches: usize,
 }
 
 impl Card {
-    fn new(card_id: usize, winning: Vec<usize>, my: Vec<usize>) -> Card {
+    pub fn new(card_id: usize, winning: Vec<usize>, my: Vec<usize>) -> Card {
         let mut matches = 0usize;
         for w in winning.iter() {
             for m in my.iter() {
@@ -20,46 +22,94 @@ impl Card {
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let card_re = Regex::new(r"Card\s*(\d+):\s*([\d ]+)\s*\|\s*([\d ]+)\s*$").unwrap();
+pub struct Day4;
 
-    let mut cards = Vec::<Card>::new();
-    for l in input.lines() {
-        for c in card_re.captures_iter(l) {
-            let card_id = c.get(1).unwrap().as_str().parse::<usize>().unwrap();
-            let winning_raw = c.get(2).unwrap().as_str();
-            let my_raw = c.get(3).unwrap().as_str();
-            let mut winning = Vec::<usize>::new();
-            for w in winning_raw.split_whitespace() {
-                winning.push(w.parse::<usize>().unwrap());
-            }
-            let mut my = Vec::<usize>::new();
-            for m in my_raw.split_whitespace() {
-                my.push(m.parse::<usize>().unwrap());
-            }
+impl Solution for Day4 {
+    type Input = Vec<Card>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Card>, ParseError> {
+        let mut cards = Vec::<Card>::new();
+        for line in parse::lines(input) {
+            let (card, numbers) = line.key_values("`Card <id>: <numbers> | <numbers>`")?;
+            let card_id = line.strip_prefix(card, "Card")?.trim_start();
+            let card_id = line.parse::<usize>(card_id, "a card id")?;
+            let (winning, my) = line.split_once(numbers, "|", "`<numbers> | <numbers>`")?;
+            let winning = line.numbers::<usize>(winning, "a number")?;
+            let my = line.numbers::<usize>(my, "a number")?;
             cards.push(Card::new(card_id, winning, my));
         }
+        Ok(cards)
     }
 
-    let mut sum_points = 0usize;
-    for c in cards.iter() {
-        let points = match c.matches {
-            0 => 0,
-            x => 2usize.pow(x as u32 - 1),
-        };
-        sum_points += points;
+    fn part1(cards: &Vec<Card>) -> usize {
+        let mut sum_points = 0usize;
+        for c in cards.iter() {
+            let points = match c.matches {
+                0 => 0,
+                x => 2usize.pow(x as u32 - 1),
+            };
+            sum_points += points;
+        }
+        sum_points
     }
 
-    let mut processed_cards = 0usize;
-    let mut card_counts = cards.iter().map(|_| 1usize).collect::<Vec<usize>>();
-    for (i, c) in cards.iter().enumerate() {
-        let count = *card_counts.get(i).unwrap();
-        processed_cards += count;
-        for i in 0..c.matches {
-            let other_c_idx = c.card_id + i;
-            card_counts[other_c_idx] += count;
+    fn part2(cards: &Vec<Card>) -> usize {
+        let mut processed_cards = 0usize;
+        let mut card_counts = cards.iter().map(|_| 1usize).collect::<Vec<usize>>();
+        for (i, c) in cards.iter().enumerate() {
+            let count = *card_counts.get(i).unwrap();
+            processed_cards += count;
+            for i in 0..c.matches {
+                let other_c_idx = c.card_id + i;
+                card_counts[other_c_idx] += count;
+            }
         }
+
+        processed_cards
     }
 
-    (format!("{sum_points}"), format!("{processed_cards}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let cards = size.unwrap_or(200);
+        let width = cards.to_string().len();
+        let mut copies = vec![1usize; cards + 1];
+        let mut out = String::new();
+        for card_id in 1..=cards {
+            let mut numbers = (1..=99).collect::<Vec<usize>>();
+            rng.shuffle(&mut numbers);
+
+            // Never win copies of cards past the end, and stop winning once a card has so many
+            // copies that the total could overflow
+            let mut matches = (0..10).filter(|_| rng.chance(0.3)).count();
+            matches = matches.min(cards - card_id);
+            if copies[card_id] > 1_000_000 {
+                matches = 0;
+            }
+            for i in 1..=matches {
+                copies[card_id + i] += copies[card_id];
+            }
+
+            let winning = &numbers[..10];
+            let mut my = numbers[..matches]
+                .iter()
+                .chain(&numbers[10..35 - matches])
+                .copied()
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut my);
+
+            let list = |ns: &[usize]| {
+                ns.iter()
+                    .map(|n| format!("{n:>2}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            out += &format!(
+                "Card {card_id:>width$}: {} | {}\n",
+                list(winning),
+                list(&my)
+            );
+        }
+        out
+    }
 }
@@ -1,24 +1,24 @@
-use std::iter::once;
-
-use regex::Regex;
+use super::{Comparison, Solution};
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
 
 #[derive(Clone, Debug)]
-struct CatRange {
-    dst_start: usize,
-    src_start: usize,
-    len: usize,
+pub struct CatRange {
+    pub dst_start: usize,
+    pub src_start: usize,
+    pub len: usize,
 }
 
 #[derive(Debug)]
-struct Map {
-    _src: String,
-    _dst: String,
-    ranges: Vec<CatRange>,
+pub struct Map {
+    pub src: String,
+    pub dst: String,
+    pub ranges: Vec<CatRange>,
 }
 
 impl Map {
-    fn new(src: String, dst: String, mut ranges: Vec<CatRange>) -> Map {
-        ranges.sort_by(|a, b| a.src_start.cmp(&b.src_start));
+    pub fn new(src: String, dst: String, mut ranges: Vec<CatRange>) -> Map {
+        ranges.sort_by_key(|r| r.src_start);
         let mut new_ranges = Vec::<CatRange>::new();
         let mut cur_src = 0usize;
         for r in ranges.iter() {
@@ -40,115 +40,232 @@ impl Map {
         });
 
         Map {
-            _src: src.to_owned(),
-            _dst: dst.to_owned(),
+            src,
+            dst,
             ranges: new_ranges,
         }
     }
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let seeds_re = Regex::new(r"seeds: (.+)").unwrap();
-    let mut lines = input.lines();
-
-    let seeds_cap = seeds_re.captures(lines.next().unwrap()).unwrap();
-    assert!(seeds_cap.len() == 2);
-    let seeds = seeds_cap
-        .get(1)
-        .unwrap()
-        .as_str()
-        .split_whitespace()
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
-
-    let mut maps = Vec::<Map>::new();
-    let map_re = Regex::new(r"(.+)-to-(.+) map:").unwrap();
-    let mut cur_map: Option<(String, String)> = None;
-    let mut cur_ranges = Vec::<CatRange>::new();
-    for line in lines.chain(once("")) {
-        if line.is_empty() {
-            if let Some((src, dst)) = &cur_map {
-                maps.push(Map::new(src.to_owned(), dst.to_owned(), cur_ranges.clone()));
-            }
-            cur_map = None;
-            cur_ranges.clear();
-        } else if cur_map.is_none() {
-            for m in map_re.captures_iter(line) {
-                cur_map = Some((
-                    m.get(1).unwrap().as_str().to_owned(),
-                    m.get(2).unwrap().as_str().to_owned(),
+#[derive(Debug)]
+pub struct Almanac {
+    pub seeds: Vec<usize>,
+    pub maps: Vec<Map>,
+}
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// The most seeds [`reference_location`] is run on before giving up.
+const REFERENCE_SEEDS: usize = 50_000_000;
+
+/// Where `seed` ends up, looked up through each map in turn without any splitting of ranges.
+fn reference_location(maps: &[Map], seed: usize) -> usize {
+    maps.iter().fold(seed, |cur, m| {
+        m.ranges
+            .iter()
+            .find(|r| cur >= r.src_start && cur - r.src_start < r.len)
+            .map_or(cur, |r| r.dst_start + (cur - r.src_start))
+    })
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Almanac;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Almanac, ParseError> {
+        let mut sections = parse::sections(input).into_iter();
+
+        let seeds_section = sections
+            .next()
+            .ok_or_else(|| ParseError::end_of_input(input, "`seeds: <numbers>`"))?;
+        let first = seeds_section[0];
+        let (key, seed_list) = first.key_values("`seeds: <numbers>`")?;
+        if key != "seeds" {
+            return Err(first.error_at(key, "`seeds`"));
+        }
+        let seeds = first.numbers::<usize>(seed_list, "a seed number")?;
+        // Part 2 reads them as the start and length of ranges
+        if !seeds.len().is_multiple_of(2) {
+            return Err(first.error_at(seed_list, "pairs of seed numbers"));
+        }
+        if let Some(extra) = seeds_section.get(1) {
+            return Err(extra.error_at(extra.text, "a blank line"));
+        }
+
+        let mut maps = Vec::<Map>::new();
+        for section in sections {
+            let header = section[0];
+            let expected = "`<category>-to-<category> map:`";
+            let name = header.strip_suffix(header.text, " map:")?;
+            let (src, dst) = header.split_once(name, "-to-", expected)?;
+
+            let mut ranges = Vec::<(parse::Line, CatRange)>::new();
+            for line in section[1..].iter() {
+                let numbers = line.numbers::<usize>(line.text, "a number")?;
+                let [dst_start, src_start, len] = numbers[..] else {
+                    return Err(line.error_at(line.text, "`<destination> <source> <length>`"));
+                };
+                if src_start.checked_add(len).is_none() || dst_start.checked_add(len).is_none() {
+                    return Err(line.error_at(line.text, "a range that fits in a usize"));
+                }
+                ranges.push((
+                    *line,
+                    CatRange {
+                        dst_start,
+                        src_start,
+                        len,
+                    },
                 ));
             }
-        } else {
-            let mut elems = line.split_whitespace();
-            cur_ranges.push(CatRange {
-                dst_start: elems.next().unwrap().parse::<usize>().unwrap(),
-                src_start: elems.next().unwrap().parse::<usize>().unwrap(),
-                len: elems.next().unwrap().parse::<usize>().unwrap(),
-            });
-            assert!(elems.next().is_none());
+            // Map::new fills the gaps between the source ranges, so they mustn't overlap
+            ranges.sort_by_key(|(_, r)| r.src_start);
+            for pair in ranges.windows(2) {
+                let ((prev_line, prev), (line, r)) = (&pair[0], &pair[1]);
+                if prev.src_start + prev.len > r.src_start {
+                    return Err(line.error_at(
+                        line.text,
+                        format!("a source range clear of line {}", prev_line.num),
+                    ));
+                }
+            }
+            let ranges = ranges.into_iter().map(|(_, r)| r).collect();
+            maps.push(Map::new(src.to_owned(), dst.to_owned(), ranges));
         }
+
+        Ok(Almanac { seeds, maps })
     }
-    assert!(cur_map.is_none());
-    assert!(cur_ranges.len() == 0);
 
-    let mut min_single_loc = usize::MAX;
-    for s in seeds.iter() {
-        let mut cur_loc = *s;
-        for m in maps.iter() {
-            for r in m.ranges.iter() {
-                if cur_loc < r.src_start {
-                    continue;
-                }
-                if cur_loc > r.src_start + r.len {
-                    continue;
+    fn part1(almanac: &Almanac) -> usize {
+        let Almanac { seeds, maps } = almanac;
+        let mut min_single_loc = usize::MAX;
+        for s in seeds.iter() {
+            let mut cur_loc = *s;
+            for m in maps.iter() {
+                for r in m.ranges.iter() {
+                    if cur_loc < r.src_start {
+                        continue;
+                    }
+                    if cur_loc > r.src_start + r.len {
+                        continue;
+                    }
+                    cur_loc = cur_loc - r.src_start + r.dst_start;
+                    break;
                 }
-                cur_loc = cur_loc - r.src_start + r.dst_start;
-                break;
             }
+            min_single_loc = min_single_loc.min(cur_loc);
         }
-        min_single_loc = min_single_loc.min(cur_loc);
+        min_single_loc
     }
 
-    assert!(seeds.len() % 2 == 0);
-    let seed_ranges = (0..seeds.len())
-        .step_by(2)
-        .map(|i| (seeds[i], seeds[i + 1]))
-        .collect::<Vec<_>>();
+    fn part2(almanac: &Almanac) -> usize {
+        let Almanac { seeds, maps } = almanac;
+        let seed_ranges = (0..seeds.len())
+            .step_by(2)
+            .map(|i| (seeds[i], seeds[i + 1]))
+            .collect::<Vec<_>>();
 
-    let mut cur_ranges = seed_ranges.clone();
+        let mut cur_ranges = seed_ranges;
 
-    for m in maps.iter() {
-        let mut next_ranges = Vec::<(usize, usize)>::new();
-        for range in cur_ranges.iter() {
-            let mut start = range.0;
-            let mut len = range.1;
+        for m in maps.iter() {
+            let mut next_ranges = Vec::<(usize, usize)>::new();
+            for range in cur_ranges.iter() {
+                let mut start = range.0;
+                let mut len = range.1;
 
-            for r in m.ranges.iter() {
-                if start >= r.src_start && start < r.src_start + r.len {
-                    let off = start - r.src_start;
+                for r in m.ranges.iter() {
+                    if start >= r.src_start && start < r.src_start + r.len {
+                        let off = start - r.src_start;
 
-                    let new_start = usize::min(r.src_start + r.len, start + len);
-                    let new_len = start + len - new_start;
-                    assert!(new_start >= start);
-                    assert!(new_len <= len);
+                        let new_start = usize::min(r.src_start + r.len, start + len);
+                        let new_len = start + len - new_start;
+                        assert!(new_start >= start);
+                        assert!(new_len <= len);
 
-                    next_ranges.push((r.dst_start + off, len - new_len));
-                    start = new_start;
-                    len = new_len;
-                }
-                if len == 0 {
-                    break;
+                        next_ranges.push((r.dst_start + off, len - new_len));
+                        start = new_start;
+                        len = new_len;
+                    }
+                    if len == 0 {
+                        break;
+                    }
                 }
             }
+            cur_ranges.clear();
+            cur_ranges.append(&mut next_ranges);
+        }
+        let mut min_pairs_loc = usize::MAX;
+        for pair in cur_ranges.iter() {
+            min_pairs_loc = usize::min(min_pairs_loc, pair.0);
         }
-        cur_ranges.clear();
-        cur_ranges.extend(next_ranges.drain(..));
+
+        min_pairs_loc
     }
-    let mut min_pairs_loc = usize::MAX;
-    for pair in cur_ranges.iter() {
-        min_pairs_loc = usize::min(min_pairs_loc, pair.0);
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let ranges = size.unwrap_or(30).max(1);
+        // About the span of the real input at its size, and small enough to check seed by seed when
+        // there are only a few ranges
+        let span = ranges.pow(3) * 160_000;
+
+        let mut out = String::from("seeds:");
+        for _ in 0..5 {
+            out += &format!(" {} {}", rng.below(span), rng.range(1..=span / 20));
+        }
+        out.push('\n');
+
+        for pair in CATEGORIES.windows(2) {
+            out += &format!("\n{}-to-{} map:\n", pair[0], pair[1]);
+
+            // Cut the span into pieces and lay them back down in a different order
+            let mut cuts = (1..ranges).map(|_| rng.below(span)).collect::<Vec<_>>();
+            cuts.extend([0, span]);
+            cuts.sort();
+            cuts.dedup();
+            let mut pieces = cuts
+                .windows(2)
+                .map(|w| (w[0], w[1] - w[0]))
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut pieces);
+            let mut dst_start = 0usize;
+            let mut lines = Vec::<String>::new();
+            for (src_start, len) in pieces {
+                lines.push(format!("{dst_start} {src_start} {len}\n"));
+                dst_start += len;
+            }
+            rng.shuffle(&mut lines);
+            out += &lines.concat();
+        }
+        out
     }
 
-    (format!("{min_single_loc}"), format!("{min_pairs_loc}"))
+    fn cross_check(almanac: &Almanac) -> Vec<Comparison> {
+        let Almanac { seeds, maps } = almanac;
+        let lowest = seeds.iter().map(|s| reference_location(maps, *s)).min();
+        let pairs = seeds.chunks_exact(2).collect::<Vec<_>>();
+        let seeds_in_pairs = pairs.iter().map(|p| p[1]).sum::<usize>();
+        let lowest_in_pairs = (seeds_in_pairs <= REFERENCE_SEEDS).then(|| {
+            pairs
+                .iter()
+                .flat_map(|p| p[0]..p[0] + p[1])
+                .map(|s| reference_location(maps, s))
+                .min()
+                .unwrap_or(usize::MAX)
+        });
+        vec![
+            Comparison::new("part 1", Self::part1(almanac), lowest.or(Some(usize::MAX))),
+            Comparison::new("part 2", Self::part2(almanac), lowest_in_pairs),
+        ]
+    }
 }
@@ -1,63 +1,110 @@
 use std::fmt::Write;
 
-struct Race {
-    t: usize,
-    d: usize,
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, Line, ParseError};
+
+pub struct Race {
+    pub t: usize,
+    pub d: usize,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut lines = input.lines();
-    let times = lines
-        .next()
-        .unwrap()
-        .replace("Time:", "")
-        .split_whitespace()
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
-    let distances = lines
-        .next()
-        .unwrap()
-        .replace("Distance:", "")
-        .split_whitespace()
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
-
-    assert!(times.len() == distances.len());
-
-    let races = times
-        .iter()
-        .zip(distances.iter())
-        .map(|(t, d)| Race { t: *t, d: *d })
-        .collect::<Vec<_>>();
-
-    let calc_win_count = |r: &Race| -> usize {
-        let mut win_count = 0usize;
-        for hold_time in 0..=r.t {
-            let rest_time = r.t - hold_time;
-            let distance = rest_time * hold_time;
-            let is_win = distance > r.d;
-            if is_win {
-                win_count += 1;
-            }
+fn calc_win_count(r: &Race) -> usize {
+    let mut win_count = 0usize;
+    for hold_time in 0..=r.t {
+        let rest_time = r.t - hold_time;
+        let distance = rest_time * hold_time;
+        let is_win = distance > r.d;
+        if is_win {
+            win_count += 1;
         }
-        win_count
-    };
+    }
+    win_count
+}
 
-    let win_counts = races.iter().map(calc_win_count).collect::<Vec<_>>();
-    let win_counts_mult = win_counts.iter().fold(1usize, |a, e| a * e);
+fn parse_values(input: &str, line: Option<Line>, label: &str) -> Result<Vec<usize>, ParseError> {
+    let line =
+        line.ok_or_else(|| ParseError::end_of_input(input, format!("`{label} <numbers>`")))?;
+    let values = line
+        .text
+        .strip_prefix(label)
+        .ok_or_else(|| line.error_at(line.text, format!("`{label} <numbers>`")))?;
+    let numbers = line.numbers(values, "a number")?;
+    if numbers.is_empty() {
+        return Err(line.end_error("a number"));
+    }
+    Ok(numbers)
+}
 
+/// The one race the races make with the spaces between their numbers taken out, if it fits in a
+/// usize.
+fn kerned(races: &[Race]) -> Option<Race> {
     let mut t_concat = String::new();
     let mut d_concat = String::new();
     for r in races.iter() {
         write!(&mut t_concat, "{}", r.t).unwrap();
         write!(&mut d_concat, "{}", r.d).unwrap();
     }
+    Some(Race {
+        t: t_concat.parse::<usize>().ok()?,
+        d: d_concat.parse::<usize>().ok()?,
+    })
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<Race>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    let race_cat = Race {
-        t: t_concat.parse::<usize>().unwrap(),
-        d: d_concat.parse::<usize>().unwrap(),
-    };
-    let cat_win_count = calc_win_count(&race_cat);
+    fn parse(input: &str) -> Result<Vec<Race>, ParseError> {
+        let mut lines = parse::lines(input);
+        let times = parse_values(input, lines.next(), "Time:")?;
+        let distances = parse_values(input, lines.next(), "Distance:")?;
+
+        if times.len() != distances.len() {
+            return Err(ParseError::whole_input(
+                format!("{} distances to match the times", times.len()),
+                distances.len(),
+            ));
+        }
 
-    (format!("{win_counts_mult}"), format!("{cat_win_count}"))
+        let races = times
+            .iter()
+            .zip(distances.iter())
+            .map(|(t, d)| Race { t: *t, d: *d })
+            .collect::<Vec<_>>();
+        if kerned(&races).is_none() {
+            return Err(ParseError::whole_input(
+                "times and distances that fit in a usize with their digits run together",
+                "too many digits",
+            ));
+        }
+        Ok(races)
+    }
+
+    fn part1(races: &Vec<Race>) -> usize {
+        let win_counts = races.iter().map(calc_win_count).collect::<Vec<_>>();
+        win_counts.iter().product()
+    }
+
+    fn part2(races: &Vec<Race>) -> usize {
+        calc_win_count(&kerned(races).unwrap())
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        // Any more races and part 2's one long race doesn't fit in a usize
+        let races = size.unwrap_or(4).clamp(1, 4);
+        let mut times = String::new();
+        let mut distances = String::new();
+        for _ in 0..races {
+            let t = rng.range(10..=99);
+            // Short of the best possible, so the record can be beaten
+            let hold = rng.range(1..=t / 2 - 1);
+            write!(&mut times, "{t:>6}").unwrap();
+            write!(&mut distances, "{:>6}", hold * (t - hold)).unwrap();
+        }
+        format!("Time:   {times}\nDistance:{distances}\n")
+    }
 }
@@ -1,7 +1,11 @@
 use std::collections::BTreeMap;
 
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+
 #[derive(Eq, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
-enum Card {
+pub enum Card {
     Ace = 14,
     King = 13,
     Queen = 12,
@@ -19,7 +23,7 @@ enum Card {
 }
 
 #[derive(Eq, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
-enum HandType {
+pub enum HandType {
     FiveOfAKind = 7,
     FourOfAKind = 6,
     FullHouse = 5,
@@ -30,65 +34,51 @@ enum HandType {
 }
 
 #[derive(Eq, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
-struct Hand {
-    hand_type: HandType,
-    cards: [Card; 5],
-    bid: usize,
+pub struct Hand {
+    pub hand_type: HandType,
+    pub cards: [Card; 5],
+    pub bid: usize,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut hands = Vec::<Hand>::new();
-    let mut hands2 = Vec::<Hand>::new();
-    for l in input.lines() {
-        let mut es = l.split_whitespace();
-        let hand = es.next().unwrap();
-        let bid = es.next().unwrap().parse::<usize>().unwrap();
-        assert!(hand.len() == 5);
-
-        hands.push(|| -> Hand {
-            fn parse(c: char) -> Card {
-                match c {
-                    'A' => Card::Ace,
-                    'K' => Card::King,
-                    'Q' => Card::Queen,
-                    'J' => Card::Jack,
-                    'T' => Card::Ten,
-                    '9' => Card::Nine,
-                    '8' => Card::Eight,
-                    '7' => Card::Seven,
-                    '6' => Card::Six,
-                    '5' => Card::Five,
-                    '4' => Card::Four,
-                    '3' => Card::Three,
-                    '2' => Card::Two,
-                    other => panic!("{other}"),
-                }
-            }
+impl Card {
+    pub fn new(c: char) -> Option<Card> {
+        Some(match c {
+            'A' => Card::Ace,
+            'K' => Card::King,
+            'Q' => Card::Queen,
+            'J' => Card::Jack,
+            'T' => Card::Ten,
+            '9' => Card::Nine,
+            '8' => Card::Eight,
+            '7' => Card::Seven,
+            '6' => Card::Six,
+            '5' => Card::Five,
+            '4' => Card::Four,
+            '3' => Card::Three,
+            '2' => Card::Two,
+            _ => return None,
+        })
+    }
+}
 
-            let mut hand_cs = hand.chars();
-            let mut next = || hand_cs.next().unwrap();
-            let cards = [
-                parse(next()),
-                parse(next()),
-                parse(next()),
-                parse(next()),
-                parse(next()),
-            ];
-
-            assert!(hand_cs.next().is_none());
-
-            let mut counts = BTreeMap::<Card, usize>::new();
-            for card in cards.iter() {
-                counts
-                    .entry(*card)
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
-            }
-            let mut fives = 0usize;
-            let mut fours = 0usize;
-            let mut threes = 0usize;
-            let mut twos = 0usize;
-            for (_, count) in counts.iter() {
+impl HandType {
+    pub fn new(cards: &[Card; 5]) -> HandType {
+        let mut counts = BTreeMap::<Card, usize>::new();
+        for card in cards.iter() {
+            counts
+                .entry(*card)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        let mut fives = 0usize;
+        let mut fours = 0usize;
+        let mut threes = 0usize;
+        let mut twos = 0usize;
+        let mut jokers = 0usize;
+        for (card, count) in counts.iter() {
+            if matches!(card, Card::Joker) {
+                jokers = *count;
+            } else {
                 match count {
                     5 => fives += 1,
                     4 => fours += 1,
@@ -97,120 +87,105 @@ pub fn main(input: &str) -> (String, String) {
                     _ => (),
                 }
             }
-            let hand_type = match (fives, fours, threes, twos) {
-                (1, 0, 0, 0) => HandType::FiveOfAKind,
-                (0, 1, 0, 0) => HandType::FourOfAKind,
-                (0, 0, 1, 1) => HandType::FullHouse,
-                (0, 0, 1, 0) => HandType::ThreeOfAKind,
-                (0, 0, 0, 2) => HandType::TwoPair,
-                (0, 0, 0, 1) => HandType::OnePair,
-                (_, _, _, _) => HandType::HighCard,
-            };
-
-            Hand {
-                hand_type,
-                cards,
-                bid,
-            }
-        }());
-
-        hands2.push(|| -> Hand {
-            fn parse(c: char) -> Card {
-                match c {
-                    'A' => Card::Ace,
-                    'K' => Card::King,
-                    'Q' => Card::Queen,
-                    'T' => Card::Ten,
-                    '9' => Card::Nine,
-                    '8' => Card::Eight,
-                    '7' => Card::Seven,
-                    '6' => Card::Six,
-                    '5' => Card::Five,
-                    '4' => Card::Four,
-                    '3' => Card::Three,
-                    '2' => Card::Two,
-                    'J' => Card::Joker,
-                    other => panic!("{other}"),
-                }
-            }
+        }
+        match (fives, fours, threes, twos, jokers) {
+            (1, 0, 0, 0, 0) => HandType::FiveOfAKind,
+            (0, 1, 0, 0, 1) => HandType::FiveOfAKind,
+            (0, 0, 1, 0, 2) => HandType::FiveOfAKind,
+            (0, 0, 0, 1, 3) => HandType::FiveOfAKind,
+            (0, 0, 0, 0, 4) => HandType::FiveOfAKind,
+            (0, 0, 0, 0, 5) => HandType::FiveOfAKind,
+            (0, 1, 0, 0, 0) => HandType::FourOfAKind,
+            (0, 0, 1, 0, 1) => HandType::FourOfAKind,
+            (0, 0, 0, 1, 2) => HandType::FourOfAKind,
+            (0, 0, 0, 0, 3) => HandType::FourOfAKind,
+            (0, 0, 1, 1, 0) => HandType::FullHouse,
+            (0, 0, 0, 2, 1) => HandType::FullHouse,
+            (0, 0, 1, 0, 0) => HandType::ThreeOfAKind,
+            (0, 0, 0, 1, 1) => HandType::ThreeOfAKind,
+            (0, 0, 0, 0, 2) => HandType::ThreeOfAKind,
+            (0, 0, 0, 2, 0) => HandType::TwoPair,
+            (0, 0, 0, 1, 0) => HandType::OnePair,
+            (0, 0, 0, 0, 1) => HandType::OnePair,
+            (_, _, _, _, _) => HandType::HighCard,
+        }
+    }
+}
 
-            let mut hand_cs = hand.chars();
-            let mut next = || hand_cs.next().unwrap();
-            let cards = [
-                parse(next()),
-                parse(next()),
-                parse(next()),
-                parse(next()),
-                parse(next()),
-            ];
-            assert!(hand_cs.next().is_none());
-
-            let mut counts = BTreeMap::<Card, usize>::new();
-            for card in cards.iter() {
-                counts
-                    .entry(*card)
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
-            }
-            let mut fives = 0usize;
-            let mut fours = 0usize;
-            let mut threes = 0usize;
-            let mut twos = 0usize;
-            let mut jokers = 0usize;
-            for (card, count) in counts.iter() {
-                if matches!(card, Card::Joker) {
-                    jokers = *count;
-                } else {
-                    match count {
-                        5 => fives += 1,
-                        4 => fours += 1,
-                        3 => threes += 1,
-                        2 => twos += 1,
-                        _ => (),
-                    }
-                }
-            }
-            let hand_type = match (fives, fours, threes, twos, jokers) {
-                (1, 0, 0, 0, 0) => HandType::FiveOfAKind,
-                (0, 1, 0, 0, 1) => HandType::FiveOfAKind,
-                (0, 0, 1, 0, 2) => HandType::FiveOfAKind,
-                (0, 0, 0, 1, 3) => HandType::FiveOfAKind,
-                (0, 0, 0, 0, 4) => HandType::FiveOfAKind,
-                (0, 0, 0, 0, 5) => HandType::FiveOfAKind,
-                (0, 1, 0, 0, 0) => HandType::FourOfAKind,
-                (0, 0, 1, 0, 1) => HandType::FourOfAKind,
-                (0, 0, 0, 1, 2) => HandType::FourOfAKind,
-                (0, 0, 0, 0, 3) => HandType::FourOfAKind,
-                (0, 0, 1, 1, 0) => HandType::FullHouse,
-                (0, 0, 0, 2, 1) => HandType::FullHouse,
-                (0, 0, 1, 0, 0) => HandType::ThreeOfAKind,
-                (0, 0, 0, 1, 1) => HandType::ThreeOfAKind,
-                (0, 0, 0, 0, 2) => HandType::ThreeOfAKind,
-                (0, 0, 0, 2, 0) => HandType::TwoPair,
-                (0, 0, 0, 1, 0) => HandType::OnePair,
-                (0, 0, 0, 0, 1) => HandType::OnePair,
-                (_, _, _, _, _) => HandType::HighCard,
-            };
-
-            Hand {
-                hand_type,
-                cards,
-                bid,
-            }
-        }());
+impl Hand {
+    pub fn new(cards: [Card; 5], bid: usize) -> Hand {
+        Hand {
+            hand_type: HandType::new(&cards),
+            cards,
+            bid,
+        }
     }
 
+    /// The same hand, with every Jack played as a Joker instead.
+    pub fn with_jokers(&self) -> Hand {
+        let cards = self.cards.map(|c| match c {
+            Card::Jack => Card::Joker,
+            other => other,
+        });
+        Hand::new(cards, self.bid)
+    }
+}
+
+fn total_winnings(mut hands: Vec<Hand>) -> usize {
     hands.sort();
-    let total_winnings = hands
+    hands
         .iter()
         .enumerate()
-        .fold(0usize, |a, (i, h)| a + (i + 1) * h.bid);
+        .fold(0usize, |a, (i, h)| a + (i + 1) * h.bid)
+}
 
-    hands2.sort();
-    let total_winnings2 = hands2
-        .iter()
-        .enumerate()
-        .fold(0usize, |a, (i, h)| a + (i + 1) * h.bid);
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<Hand>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Hand>, ParseError> {
+        let mut hands = Vec::<Hand>::new();
+        for line in parse::lines(input) {
+            let mut es = line.text.split_whitespace();
+            let hand = es.next().ok_or_else(|| line.end_error("a hand"))?;
+            let bid = es.next().ok_or_else(|| line.end_error("a bid"))?;
+            let bid = line.parse::<usize>(bid, "a bid")?;
+            if let Some(extra) = es.next() {
+                return Err(line.error_at(extra, "end of line"));
+            }
 
-    (format!("{total_winnings}"), format!("{total_winnings2}"))
+            let col = line.column_of(hand) - 1;
+            let cards = hand
+                .chars()
+                .enumerate()
+                .map(|(i, c)| Card::new(c).ok_or_else(|| line.char_error(col + i, c, "a card")))
+                .collect::<Result<Vec<_>, _>>()?;
+            let cards: [Card; 5] = cards
+                .try_into()
+                .map_err(|_| line.error_at(hand, "a hand of five cards"))?;
+
+            hands.push(Hand::new(cards, bid));
+        }
+        Ok(hands)
+    }
+
+    fn part1(hands: &Vec<Hand>) -> usize {
+        total_winnings(hands.clone())
+    }
+
+    fn part2(hands: &Vec<Hand>) -> usize {
+        total_winnings(hands.iter().map(|h| h.with_jokers()).collect())
+    }
+
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let mut out = String::new();
+        for _ in 0..size.unwrap_or(1000) {
+            let hand = rng.string(b"23456789TJQKA", 5);
+            out += &format!("{hand} {}\n", rng.range(1..=1000));
+        }
+        out
+    }
 }
@@ -1,89 +1,227 @@
 use std::collections::BTreeMap;
 
-use regex::Regex;
+use super::{Comparison, MaybeAnswer, Solution};
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
 
-enum Dir {
+#[derive(Debug)]
+pub enum Dir {
     L,
     R,
 }
 
-pub fn main(input: &str) -> (String, String) {
-    let mut lines = input.lines();
-    let dirs = lines
-        .next()
-        .unwrap()
-        .chars()
-        .map(|c| match c {
-            'L' => Dir::L,
-            'R' => Dir::R,
-            other => panic!("{other}"),
-        })
-        .collect::<Vec<_>>();
-    lines.next();
-
-    let mut nodes = BTreeMap::<String, (String, String)>::new();
-    let node_re = Regex::new(r"(...) = \((...), (...)\)").unwrap();
-    for line in lines {
-        let c = node_re.captures(line).unwrap();
-        assert!(c.len() == 4);
-        let cur = c.get(1).unwrap().as_str().to_owned();
-        let left = c.get(2).unwrap().as_str().to_owned();
-        let right = c.get(3).unwrap().as_str().to_owned();
-        nodes.insert(cur.to_owned(), (left.to_owned(), right.to_owned()));
+#[derive(Debug)]
+pub struct Network {
+    pub dirs: Vec<Dir>,
+    pub nodes: BTreeMap<String, (String, String)>,
+}
+
+/// The steps from `start_node` to an end, or `None` if it never gets to one.
+fn calc_count<P>(network: &Network, start_node: &str, is_end_node: P) -> Option<usize>
+where
+    P: Fn(&str) -> bool,
+{
+    let Network { dirs, nodes } = network;
+    let mut cur_node = nodes.get_key_value(start_node)?.0.as_str();
+
+    // Past this many steps the walk has been at every node at every point in the directions, so
+    // it's going round a loop without an end on it
+    let limit = nodes.len() * dirs.len();
+    for count in 1..=limit {
+        // Parse checked that every node leads to nodes that are defined
+        let next_nodes = &nodes[cur_node];
+        cur_node = match dirs[(count - 1) % dirs.len()] {
+            Dir::L => &next_nodes.0,
+            Dir::R => &next_nodes.1,
+        };
+        if is_end_node(cur_node) {
+            return Some(count);
+        }
     }
+    None
+}
 
-    fn calc_count<'a, P>(
-        nodes: &'a BTreeMap<String, (String, String)>,
-        dirs: &Vec<Dir>,
-        start_node: &'a str,
-        is_end_node: P,
-    ) -> (&'a str, usize)
-    where
-        P: Fn(&str) -> bool,
-    {
-        let mut cur_node = start_node;
-
-        let mut count = 0usize;
-        loop {
-            let dir = &dirs[count % dirs.len()];
-            count += 1;
+const PRIMES: [usize; 14] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
 
-            let next_nodes = nodes.get(cur_node);
-            let next_nodes = match next_nodes {
-                Some(n) => n,
-                None => return ("", usize::MAX),
-            };
-            cur_node = match dir {
+/// Letters for the generated node names that don't make them a start or an end.
+const NAME_CHARS: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+
+/// The most steps [`reference_steps`] takes before giving up.
+const REFERENCE_STEPS: usize = 100_000_000;
+
+/// Part 2 by walking every ghost at once until they're all on an end.
+fn reference_steps(network: &Network) -> Option<usize> {
+    let Network { dirs, nodes } = network;
+    let mut cur_nodes = nodes
+        .keys()
+        .filter(|k| k.ends_with('A'))
+        .map(|k| k.as_str())
+        .collect::<Vec<_>>();
+    for step in 0..REFERENCE_STEPS {
+        for node in cur_nodes.iter_mut() {
+            let next_nodes = &nodes[*node];
+            *node = match dirs[step % dirs.len()] {
                 Dir::L => &next_nodes.0,
                 Dir::R => &next_nodes.1,
             };
-            if is_end_node(cur_node) {
-                return (cur_node, count);
+        }
+        if cur_nodes.iter().all(|n| n.ends_with('Z')) {
+            return Some(step + 1);
+        }
+    }
+    None
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Network;
+    type Answer1 = MaybeAnswer<usize>;
+    type Answer2 = MaybeAnswer<usize>;
+
+    fn parse(input: &str) -> Result<Network, ParseError> {
+        let mut lines = parse::lines(input);
+        let first = lines
+            .next()
+            .ok_or_else(|| ParseError::end_of_input(input, "a line of directions"))?;
+        let dirs = first
+            .text
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                'L' => Ok(Dir::L),
+                'R' => Ok(Dir::R),
+                other => Err(first.char_error(i, other, "`L` or `R`")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if dirs.is_empty() {
+            return Err(first.end_error("`L` or `R`"));
+        }
+        let blank = lines
+            .next()
+            .ok_or_else(|| ParseError::end_of_input(input, "a blank line"))?;
+        if !blank.text.is_empty() {
+            return Err(blank.error_at(blank.text, "a blank line"));
+        }
+
+        let mut nodes = BTreeMap::<String, (String, String)>::new();
+        let mut targets = Vec::new();
+        for line in lines {
+            let expected = "`AAA = (BBB, CCC)`";
+            let (cur, next) = line.split_once(line.text, " = ", expected)?;
+            let next = line.strip_prefix(next, "(")?;
+            let next = line.strip_suffix(next, ")")?;
+            let [left, right] = line.tuple::<String, 2>(next, "a node name")?;
+            nodes.insert(cur.to_owned(), (left, right));
+            targets.push((line, next));
+        }
+        // Every node leads somewhere, so the walks never fall off the network
+        for (line, next) in targets {
+            for name in next.split(',') {
+                let name = name.trim();
+                if !nodes.contains_key(name) {
+                    return Err(line.error_at(name, "a node defined on its own line"));
+                }
             }
         }
+        if !nodes.keys().any(|k| k.ends_with('A')) {
+            return Err(ParseError::whole_input(
+                "a node ending `A` to start from",
+                "none",
+            ));
+        }
+
+        Ok(Network { dirs, nodes })
     }
 
-    let count = calc_count(&nodes, &dirs, "AAA", |f| f == "ZZZ").1;
+    fn part1(network: &Network) -> MaybeAnswer<usize> {
+        MaybeAnswer(calc_count(network, "AAA", |f| f == "ZZZ"))
+    }
 
-    let cur_nodes = nodes
-        .iter()
-        .filter(|(k, _)| k.ends_with("A"))
-        .map(|(k, _)| k.as_str())
-        .collect::<Vec<_>>();
+    fn part2(network: &Network) -> MaybeAnswer<usize> {
+        let cur_nodes = network
+            .nodes
+            .keys()
+            .filter(|k| k.ends_with('A'))
+            .map(|k| k.as_str())
+            .collect::<Vec<_>>();
 
-    let cur_node_counts = cur_nodes
-        .iter()
-        .map(|n| calc_count(&nodes, &dirs, n, |f| f.ends_with("Z")).1)
-        .collect::<Vec<_>>();
+        let cur_node_counts = cur_nodes
+            .iter()
+            .map(|n| calc_count(network, n, |f| f.ends_with('Z')))
+            .collect::<Option<Vec<_>>>();
+        let Some(cur_node_counts) = cur_node_counts else {
+            return MaybeAnswer(None);
+        };
 
-    let mut count2 = 0usize;
-    let first = *cur_node_counts.first().unwrap();
-    loop {
-        count2 += first;
-        if cur_node_counts.iter().all(|n| count2 % n == 0) {
-            break;
+        let mut count2 = 0usize;
+        let first = *cur_node_counts.first().unwrap();
+        loop {
+            count2 += first;
+            if cur_node_counts.iter().all(|n| count2.is_multiple_of(*n)) {
+                break;
+            }
         }
+        MaybeAnswer(Some(count2))
     }
 
-    (format!("{count}"), format!("{count2}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        // Part 2 steps through multiples of one loop length, and with any more ghosts the
+        // product of the others' primes is too many steps to finish
+        let ghosts = size.unwrap_or(6).clamp(1, 6);
+        let dirs_len = rng.range(5..=13);
+        let dirs = rng.string(b"LR", dirs_len);
+        let mut primes = PRIMES;
+        rng.shuffle(&mut primes);
+
+        // Each ghost walks a loop from its start to its end and back round to the node after its
+        // start, a whole number of times through the directions long. That makes the steps to
+        // each end a multiple of its loop length, like the real input.
+        let loop_lens = primes[..ghosts]
+            .iter()
+            .map(|p| p * dirs_len)
+            .collect::<Vec<_>>();
+        let middle = rng.names(loop_lens.iter().map(|l| l - 1).sum(), 3, NAME_CHARS);
+        let prefixes = rng.names(ghosts, 2, NAME_CHARS);
+        let mut middle = middle.into_iter();
+
+        let mut loops = Vec::<Vec<String>>::new();
+        for (i, len) in loop_lens.iter().enumerate() {
+            let (start, end) = if i == 0 {
+                ("AAA".to_owned(), "ZZZ".to_owned())
+            } else {
+                (format!("{}A", prefixes[i]), format!("{}Z", prefixes[i]))
+            };
+            let mut nodes = vec![start];
+            nodes.extend(middle.by_ref().take(len - 1));
+            nodes.push(end);
+            loops.push(nodes);
+        }
+
+        let all = loops.concat();
+        let mut lines = Vec::<String>::new();
+        for nodes in loops.iter() {
+            for (i, node) in nodes.iter().enumerate() {
+                // The end goes back round to the node after the start
+                let next = nodes.get(i + 1).unwrap_or(&nodes[1]);
+                // Never taken, so can be anywhere
+                let other = rng.pick(&all);
+                let (left, right) = match dirs.as_bytes()[i % dirs_len] {
+                    b'L' => (next, other),
+                    _ => (other, next),
+                };
+                lines.push(format!("{node} = ({left}, {right})\n"));
+            }
+        }
+        rng.shuffle(&mut lines);
+        format!("{dirs}\n\n{}", lines.concat())
+    }
+
+    fn cross_check(network: &Network) -> Vec<Comparison> {
+        vec![Comparison::new(
+            "part 2",
+            Self::part2(network),
+            reference_steps(network),
+        )]
+    }
 }
@@ -1,42 +1,86 @@
-pub fn main(input: &str) -> (String, String) {
-    let mut extrap_summed = 0isize;
-    let mut pre_extrap_summed = 0isize;
-    for l in input.lines() {
-        let nums = l
-            .split_whitespace()
-            .map(|s| s.parse::<isize>().unwrap())
-            .collect::<Vec<_>>();
-
-        let mut diffs = vec![nums];
-        loop {
-            let last = diffs.last().unwrap();
-            if last.iter().all(|v| *v == 0) {
-                break;
-            }
-            let mut next_diffs = Vec::<isize>::new();
-            for i in 0..last.len() - 1 {
-                next_diffs.push(last[i + 1] - last[i]);
-            }
-            diffs.push(next_diffs);
+use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+
+fn calc_diffs(nums: &[isize]) -> Vec<Vec<isize>> {
+    let mut diffs = vec![nums.to_vec()];
+    loop {
+        let last = diffs.last().unwrap();
+        if last.iter().all(|v| *v == 0) {
+            break;
         }
+        let mut next_diffs = Vec::<isize>::new();
+        for i in 0..last.len() - 1 {
+            next_diffs.push(last[i + 1] - last[i]);
+        }
+        diffs.push(next_diffs);
+    }
+    diffs
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Vec<Vec<isize>>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> Result<Vec<Vec<isize>>, ParseError> {
+        parse::lines(input)
+            .map(|line| line.numbers::<isize>(line.text, "a number"))
+            .collect()
+    }
+
+    fn part1(histories: &Vec<Vec<isize>>) -> isize {
+        let mut extrap_summed = 0isize;
+        for nums in histories.iter() {
+            let mut diffs = calc_diffs(nums);
 
-        diffs.last_mut().unwrap().push(0);
+            diffs.last_mut().unwrap().push(0);
 
-        for i in (0..diffs.len() - 1).rev() {
-            let next_last = *diffs[i + 1].last().unwrap();
-            let cur = &mut diffs[i];
-            cur.push(cur.last().unwrap() + next_last);
+            for i in (0..diffs.len() - 1).rev() {
+                let next_last = *diffs[i + 1].last().unwrap();
+                let cur = &mut diffs[i];
+                cur.push(cur.last().unwrap() + next_last);
+            }
+            extrap_summed += diffs.first().unwrap().last().unwrap();
         }
-        extrap_summed += diffs.first().unwrap().last().unwrap();
+        extrap_summed
+    }
+
+    fn part2(histories: &Vec<Vec<isize>>) -> isize {
+        let mut pre_extrap_summed = 0isize;
+        for nums in histories.iter() {
+            let mut diffs = calc_diffs(nums);
 
-        diffs.last_mut().unwrap().insert(0, 0);
-        for i in (0..diffs.len() - 1).rev() {
-            let next_first = *diffs[i + 1].first().unwrap();
-            let cur = &mut diffs[i];
-            cur.insert(0, cur.first().unwrap() - next_first);
+            diffs.last_mut().unwrap().insert(0, 0);
+            for i in (0..diffs.len() - 1).rev() {
+                let next_first = *diffs[i + 1].first().unwrap();
+                let cur = &mut diffs[i];
+                cur.insert(0, cur.first().unwrap() - next_first);
+            }
+            pre_extrap_summed += diffs.first().unwrap().first().unwrap();
         }
-        pre_extrap_summed += diffs.first().unwrap().first().unwrap();
+        pre_extrap_summed
     }
 
-    (format!("{extrap_summed}"), format!("{pre_extrap_summed}"))
+    fn generate(rng: &mut Rng, size: Option<usize>) -> String {
+        let mut out = String::new();
+        for _ in 0..size.unwrap_or(200) {
+            // Built up from a row of differences that's all the same
+            let degree = rng.range(0..=6);
+            let mut values = vec![rng.range_i64(-5..=5) as isize; 21 - degree];
+            for _ in 0..degree {
+                let mut next = vec![rng.range_i64(-20..=20) as isize];
+                for d in values.iter() {
+                    next.push(next.last().unwrap() + d);
+                }
+                values = next;
+            }
+            let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            out += &values.join(" ");
+            out.push('\n');
+        }
+        out
+    }
 }
@@ -0,0 +1,155 @@
+//! Compass directions and positions on an unbounded plane, with `y` growing downwards as it does
+//! in the puzzle inputs.
+
+/// One of the four directions, listed clockwise from `Up`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    pub fn turn_right(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Right,
+            Dir::Right => Dir::Down,
+            Dir::Down => Dir::Left,
+            Dir::Left => Dir::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Left,
+            Dir::Right => Dir::Up,
+            Dir::Down => Dir::Right,
+            Dir::Left => Dir::Down,
+        }
+    }
+
+    pub fn reverse(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Right => Dir::Left,
+            Dir::Down => Dir::Up,
+            Dir::Left => Dir::Right,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Dir::Up | Dir::Down)
+    }
+
+    /// The change in `(x, y)` from one step in this direction.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Dir::Up => (0, -1),
+            Dir::Right => (1, 0),
+            Dir::Down => (0, 1),
+            Dir::Left => (-1, 0),
+        }
+    }
+
+    /// `pos` moved one step, unless that would take either coordinate below zero.
+    pub fn step(self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        let (dx, dy) = self.offset();
+        Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+    }
+
+    /// The letter [`Dir::from_letter`] reads.
+    pub fn letter(self) -> char {
+        match self {
+            Dir::Up => 'U',
+            Dir::Right => 'R',
+            Dir::Down => 'D',
+            Dir::Left => 'L',
+        }
+    }
+
+    /// `U`, `R`, `D` or `L`.
+    pub fn from_letter(c: char) -> Option<Dir> {
+        match c {
+            'U' => Some(Dir::Up),
+            'R' => Some(Dir::Right),
+            'D' => Some(Dir::Down),
+            'L' => Some(Dir::Left),
+            _ => None,
+        }
+    }
+
+    /// The arrow [`Dir::from_arrow`] reads.
+    pub fn arrow(self) -> char {
+        match self {
+            Dir::Up => '^',
+            Dir::Right => '>',
+            Dir::Down => 'v',
+            Dir::Left => '<',
+        }
+    }
+
+    /// `^`, `>`, `v` or `<`.
+    pub fn from_arrow(c: char) -> Option<Dir> {
+        match c {
+            '^' => Some(Dir::Up),
+            '>' => Some(Dir::Right),
+            'v' => Some(Dir::Down),
+            '<' => Some(Dir::Left),
+            _ => None,
+        }
+    }
+
+    /// The digit [`Dir::from_digit`] reads.
+    pub fn digit(self) -> char {
+        match self {
+            Dir::Right => '0',
+            Dir::Down => '1',
+            Dir::Left => '2',
+            Dir::Up => '3',
+        }
+    }
+
+    /// `0` to `3` for right, down, left and up.
+    pub fn from_digit(c: char) -> Option<Dir> {
+        match c {
+            '0' => Some(Dir::Right),
+            '1' => Some(Dir::Down),
+            '2' => Some(Dir::Left),
+            '3' => Some(Dir::Up),
+            _ => None,
+        }
+    }
+}
+
+/// A position that can go negative, for walking without a grid to stay inside.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Pos {
+    pub fn new(x: isize, y: isize) -> Pos {
+        Pos { x, y }
+    }
+
+    /// This position moved `n` steps in direction `d`.
+    pub fn step(self, d: Dir, n: isize) -> Pos {
+        let (dx, dy) = d.offset();
+        Pos::new(self.x + dx * n, self.y + dy * n)
+    }
+
+    /// As a grid position, if neither coordinate is negative.
+    pub fn to_unsigned(self) -> Option<(usize, usize)> {
+        Some((self.x.try_into().ok()?, self.y.try_into().ok()?))
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((x, y): (usize, usize)) -> Pos {
+        Pos::new(x as isize, y as isize)
+    }
+}
@@ -0,0 +1,177 @@
+//! Made up puzzle inputs of any size, for seeing how the days cope with more than the real input.
+//!
+//! Each day's [`Solution::generate`](crate::days::Solution::generate) writes an input in the
+//! format its parser reads, shaped so that the puzzle has an answer. Everything random comes
+//! from an [`Rng`] seeded by the caller, so the same seed always gives the same input.
+
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::dir::Dir;
+
+/// A small, fast and entirely predictable random number generator (splitmix64).
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0);
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, r: RangeInclusive<usize>) -> usize {
+        r.start() + self.below(r.end() - r.start() + 1)
+    }
+
+    pub fn range_i64(&mut self, r: RangeInclusive<i64>) -> i64 {
+        r.start() + (self.next_u64() % (r.end() - r.start() + 1) as u64) as i64
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// `len` random characters out of `alphabet`.
+    pub fn string(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len).map(|_| *self.pick(alphabet) as char).collect()
+    }
+
+    /// `count` different strings of `len` characters out of `alphabet`.
+    pub fn names(&mut self, count: usize, len: usize, alphabet: &[u8]) -> Vec<String> {
+        assert!((count as f64) < (alphabet.len() as f64).powi(len as i32));
+        let mut seen = HashSet::<String>::new();
+        let mut names = Vec::<String>::new();
+        while names.len() < count {
+            let name = self.string(alphabet, len);
+            if seen.insert(name.clone()) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+pub const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// A closed loop that never touches itself, as the direction and length of each straight run,
+/// going round a random blob about `width` by `height` runs across.
+///
+/// Each step of the blob's outline is stretched to a length picked from `stretch`, so parallel
+/// runs are never closer than its start.
+pub fn closed_loop(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    stretch: RangeInclusive<usize>,
+) -> Vec<(Dir, usize)> {
+    let (width, height) = (width.max(1), height.max(1));
+
+    // Grow a random spanning tree over about two thirds of a width by height grid of blocks
+    let mut in_tree = vec![false; width * height];
+    let mut tree = HashSet::<((usize, usize), Dir)>::new();
+    let start = (rng.below(width), rng.below(height));
+    in_tree[start.1 * width + start.0] = true;
+    let mut edges = Dir::ALL.map(|d| (start, d)).to_vec();
+    let target = (width * height * 2).div_ceil(3);
+    let mut size = 1;
+    while size < target && !edges.is_empty() {
+        let (from, d) = edges.swap_remove(rng.below(edges.len()));
+        let Some(to) = d.step(from).filter(|&(x, y)| x < width && y < height) else {
+            continue;
+        };
+        if in_tree[to.1 * width + to.0] {
+            continue;
+        }
+        in_tree[to.1 * width + to.0] = true;
+        tree.insert((from, d));
+        tree.insert((to, d.reverse()));
+        edges.extend(Dir::ALL.map(|d| (to, d)));
+        size += 1;
+    }
+
+    // Walk around the outside of the tree, through the corners of each block's 2x2 cells. Each
+    // cell is left towards the side of its block that isn't joined to the next block along.
+    let step_from = |(x, y): (usize, usize)| -> Dir {
+        let block = (x / 2, y / 2);
+        let joined = |d: Dir| tree.contains(&(block, d));
+        match (x % 2, y % 2) {
+            (0, 0) if joined(Dir::Up) => Dir::Up,
+            (0, 0) => Dir::Right,
+            (1, 0) if joined(Dir::Right) => Dir::Right,
+            (1, 0) => Dir::Down,
+            (1, 1) if joined(Dir::Down) => Dir::Down,
+            (1, 1) => Dir::Left,
+            (0, 1) if joined(Dir::Left) => Dir::Left,
+            _ => Dir::Up,
+        }
+    };
+    let first = (start.0 * 2, start.1 * 2);
+    let mut steps = Vec::<Dir>::new();
+    let mut pos = first;
+    loop {
+        let d = step_from(pos);
+        steps.push(d);
+        pos = d.step(pos).unwrap();
+        if pos == first {
+            break;
+        }
+    }
+
+    // Start on a corner, so the first run isn't split across both ends
+    let corner = (0..steps.len())
+        .find(|&i| steps[i] != steps[(i + steps.len() - 1) % steps.len()])
+        .unwrap();
+    steps.rotate_left(corner);
+
+    let xs = (0..=width * 2)
+        .map(|_| rng.range(stretch.clone()))
+        .collect::<Vec<_>>();
+    let ys = (0..=height * 2)
+        .map(|_| rng.range(stretch.clone()))
+        .collect::<Vec<_>>();
+    let mut runs = Vec::<(Dir, usize)>::new();
+    let mut pos = first;
+    for _ in 0..corner {
+        pos = step_from(pos).step(pos).unwrap();
+    }
+    for d in steps {
+        let len = match d {
+            Dir::Right => xs[pos.0],
+            Dir::Left => xs[pos.0 - 1],
+            Dir::Down => ys[pos.1],
+            Dir::Up => ys[pos.1 - 1],
+        };
+        match runs.last_mut() {
+            Some((last, n)) if *last == d => *n += len,
+            _ => runs.push((d, len)),
+        }
+        pos = d.step(pos).unwrap();
+    }
+    runs
+}
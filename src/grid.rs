@@ -0,0 +1,355 @@
+//! A rectangular grid of cells stored row by row in one `Vec`, addressed by `(x, y)` with `x`
+//! the column and `y` the row, counting from the top left.
+
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use crate::dir::Dir;
+use crate::parse::{self, Line, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const OFFSETS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const OFFSETS8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// An error pointing at the cell at `(x, y)` of a grid parsed from `input`.
+pub fn cell_error(input: &str, (x, y): (usize, usize), expected: impl Display) -> ParseError {
+    let line = parse::lines(input).nth(y).unwrap();
+    line.char_error(x, line.text.chars().nth(x).unwrap(), expected)
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(width * height, cells.len());
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn filled(width: usize, height: usize, cell: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::new(width, height, vec![cell; width * height])
+    }
+
+    /// Parses one row per line, mapping each character to a cell with `f`. Every line must be the
+    /// same length, and `f` returning `None` is reported as an error expecting `expected`.
+    pub fn parse(
+        input: &str,
+        expected: &str,
+        f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        Grid::parse_lines(parse::lines(input), expected, f)
+    }
+
+    /// Like [`Grid::parse`], for a grid that's only part of the input.
+    pub fn parse_lines<'a>(
+        lines: impl IntoIterator<Item = Line<'a>>,
+        expected: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::<T>::new();
+        for line in lines {
+            let start = cells.len();
+            for (i, c) in line.text.chars().enumerate() {
+                cells.push(f(c).ok_or_else(|| line.char_error(i, c, expected))?);
+            }
+            let len = cells.len() - start;
+            match width {
+                None => width = Some(len),
+                Some(w) if w == len => {}
+                Some(w) if len < w => return Err(line.end_error(format!("a row of {w} cells"))),
+                Some(w) => {
+                    let c = line.text.chars().nth(w).unwrap();
+                    return Err(line.char_error(w, c, format!("a row of {w} cells")));
+                }
+            }
+            height += 1;
+        }
+        Ok(Grid::new(width.unwrap_or(0), height, cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.1 * self.width + pos.0])
+        } else {
+            None
+        }
+    }
+
+    /// `pos` moved by `(dx, dy)`, if that's still inside the grid.
+    pub fn offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// `pos` moved one step in direction `d`, if that's still inside the grid.
+    pub fn step(&self, pos: (usize, usize), d: Dir) -> Option<(usize, usize)> {
+        self.offset(pos, d.offset())
+    }
+
+    /// The up to 4 positions sharing an edge with `pos`, clockwise from the one above.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS4.iter().filter_map(move |d| self.offset(pos, *d))
+    }
+
+    /// The up to 8 positions sharing an edge or corner with `pos`, clockwise from the one above.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS8.iter().filter_map(move |d| self.offset(pos, *d))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// The position of the first cell, row by row, matching `pred`.
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter().find(|(_, c)| pred(c)).map(|(pos, _)| pos)
+    }
+
+    /// The grid parsed from `input`, unless it has no cells at all.
+    pub fn non_empty(self, input: &str) -> Result<Grid<T>, ParseError> {
+        if self.cells.is_empty() {
+            Err(ParseError::end_of_input(input, "a row of cells"))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// The position of the only cell matching `pred` in the grid parsed from `input`, where
+    /// there should be exactly one `what`. None is an error about the whole input, and another is
+    /// an error pointing at it.
+    pub fn find_only(
+        &self,
+        input: &str,
+        what: &str,
+        mut pred: impl FnMut(&T) -> bool,
+    ) -> Result<(usize, usize), ParseError> {
+        let mut found = self.iter().filter(|(_, c)| pred(c)).map(|(pos, _)| pos);
+        let only = found
+            .next()
+            .ok_or_else(|| ParseError::whole_input(format!("one {what}"), "none"))?;
+        match found.next() {
+            None => Ok(only),
+            Some(pos) => Err(cell_error(input, pos, format!("only one {what}"))),
+        }
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// The grid mirrored along its diagonal, so rows become columns.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Grid::new(self.height, self.width, cells)
+    }
+
+    /// The grid turned a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .flat_map(|x| (0..self.height).rev().map(move |y| self[(x, y)].clone()))
+            .collect();
+        Grid::new(self.height, self.width, cells)
+    }
+
+    /// The grid turned a quarter turn anticlockwise.
+    pub fn rotate_ccw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Grid::new(self.height, self.width, cells)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside {width}x{height} grid"))
+    }
+}
+
+/// One line per row, with each cell written by its own `Display`.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid wider than it is tall, with one letter per cell so moves are easy to follow.
+    fn letters() -> Grid<char> {
+        Grid::parse("abcd\nefgh\nijkl\n", "a letter", Some).unwrap()
+    }
+
+    #[test]
+    fn transpose_swaps_rows_and_columns() {
+        let t = letters().transpose();
+        assert_eq!((t.width(), t.height()), (3, 4));
+        assert_eq!(t.to_string(), "aei\nbfj\ncgk\ndhl\n");
+        assert_eq!(t.transpose(), letters());
+    }
+
+    #[test]
+    fn rotations_turn_a_quarter_each_way() {
+        let cw = letters().rotate_cw();
+        assert_eq!((cw.width(), cw.height()), (3, 4));
+        assert_eq!(cw.to_string(), "iea\njfb\nkgc\nlhd\n");
+
+        let ccw = letters().rotate_ccw();
+        assert_eq!((ccw.width(), ccw.height()), (3, 4));
+        assert_eq!(ccw.to_string(), "dhl\ncgk\nbfj\naei\n");
+
+        assert_eq!(cw.rotate_ccw(), letters());
+        assert_eq!(cw.rotate_cw(), ccw.rotate_ccw());
+        assert_eq!(cw.rotate_cw().rotate_cw(), ccw);
+    }
+
+    #[test]
+    fn neighbours_stay_inside_the_grid() {
+        let g = letters();
+        for (pos, four, eight) in [
+            // Top left corner
+            ((0, 0), &[(1, 0), (0, 1)][..], &[(1, 0), (1, 1), (0, 1)][..]),
+            // Bottom right corner
+            ((3, 2), &[(3, 1), (2, 2)], &[(3, 1), (2, 2), (2, 1)]),
+            // Top edge
+            (
+                (2, 0),
+                &[(3, 0), (2, 1), (1, 0)],
+                &[(3, 0), (3, 1), (2, 1), (1, 1), (1, 0)],
+            ),
+            // Right edge
+            (
+                (3, 1),
+                &[(3, 0), (3, 2), (2, 1)],
+                &[(3, 0), (3, 2), (2, 2), (2, 1), (2, 0)],
+            ),
+            // Inside
+            (
+                (1, 1),
+                &[(1, 0), (2, 1), (1, 2), (0, 1)],
+                &[
+                    (1, 0),
+                    (2, 0),
+                    (2, 1),
+                    (2, 2),
+                    (1, 2),
+                    (0, 2),
+                    (0, 1),
+                    (0, 0),
+                ],
+            ),
+        ] {
+            assert_eq!(g.neighbours4(pos).collect::<Vec<_>>(), four, "{pos:?}");
+            assert_eq!(g.neighbours8(pos).collect::<Vec<_>>(), eight, "{pos:?}");
+        }
+    }
+}
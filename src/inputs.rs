@@ -0,0 +1,88 @@
+use std::fmt::Display;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::days::DayId;
+
+/// Where inputs live relative to the repository root, as `<year>/<day>/<variant>.txt`.
+pub const INPUTS_DIR: &str = "./inputs";
+
+/// Overrides [`INPUTS_DIR`] for the command line runner.
+pub const INPUTS_ENV: &str = "AOC23_INPUTS";
+
+pub fn day_dir(root: &Path, day: DayId) -> PathBuf {
+    root.join(day.year.to_string()).join(day.name())
+}
+
+pub fn input_path(root: &Path, day: DayId, variant: &str) -> PathBuf {
+    day_dir(root, day).join(format!("{variant}.txt"))
+}
+
+/// The variants available for a day, i.e. the stems of its `.txt` files, sorted by name.
+pub fn variants(root: &Path, day: DayId) -> Vec<String> {
+    let mut variants = Vec::<String>::new();
+    let Ok(entries) = std::fs::read_dir(day_dir(root, day)) else {
+        return variants;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "txt") {
+            if let Some(stem) = path.file_stem() {
+                variants.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    variants.sort();
+    variants
+}
+
+/// Whether an input argument names a variant, rather than `-` or something that looks like a path,
+/// i.e. has a directory separator or an extension.
+pub fn is_variant(input: &str) -> bool {
+    input != "-" && !input.contains(['/', std::path::MAIN_SEPARATOR]) && !input.contains('.')
+}
+
+/// Where a day's input is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Source {
+    /// Resolves an input argument: `-` is stdin, a variant name is looked up under `root`, and
+    /// anything else is taken as a path to the input file.
+    pub fn resolve(root: &Path, day: DayId, input: &str) -> Source {
+        if input == "-" {
+            Source::Stdin
+        } else if is_variant(input) {
+            Source::File(input_path(root, day, input))
+        } else {
+            Source::File(PathBuf::from(input))
+        }
+    }
+
+    /// The path to report errors against.
+    pub fn path(&self) -> &Path {
+        match self {
+            Source::Stdin => Path::new("<stdin>"),
+            Source::File(path) => path,
+        }
+    }
+
+    pub fn read(&self) -> Result<String, String> {
+        let mut contents = String::new();
+        let read = match self {
+            Source::Stdin => std::io::stdin().read_to_string(&mut contents).map(|_| ()),
+            Source::File(path) => std::fs::read_to_string(path).map(|s| contents = s),
+        };
+        read.map_err(|e| format!("{self}: {e}"))?;
+        Ok(contents)
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path().display())
+    }
+}
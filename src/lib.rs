@@ -0,0 +1,20 @@
+//! Advent of Code solutions, for 2023 and any later years added alongside.
+//!
+//! Each day lives in [`days`] as a module exposing its parsed model types and a
+//! `DayN` type implementing [`days::Solution`]. [`days::DAYS`] lists every day by
+//! year and day for callers that want to drive a day without knowing its types.
+
+pub mod answers;
+pub mod days;
+pub mod dir;
+pub mod generate;
+pub mod grid;
+pub mod inputs;
+pub mod json;
+pub mod log;
+pub mod parse;
+pub mod render;
+pub mod runner;
+pub mod scaffold;
+pub mod search;
+pub mod trace;
@@ -0,0 +1,111 @@
+//! Diagnostic messages from the days, written to stderr so they never get mixed in with the
+//! answers.
+//!
+//! Nothing is written until [`set_level`] raises the level above [`Level::Off`], which `-v` does,
+//! and messages are only built for levels that are on. [`set_days`] narrows that down to the days
+//! named with `--log`, going by the day each thread is running in [`for_day`].
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+use crate::days::DayId;
+
+/// How much to say, from nothing at all to everything.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    /// A line or two per part, like the cost of a path.
+    Info,
+    /// Whole grids and paths.
+    Debug,
+    /// Every step of a loop.
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 4] = [Level::Off, Level::Info, Level::Debug, Level::Trace];
+
+    /// The level for `-v` given `count` times.
+    pub fn from_verbosity(count: u8) -> Level {
+        Level::ALL[(count as usize).min(Level::ALL.len() - 1)]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    Level::ALL[LEVEL.load(Ordering::Relaxed) as usize]
+}
+
+/// The only days to write messages from, or every day if empty.
+static DAYS: RwLock<Vec<DayId>> = RwLock::new(Vec::new());
+
+thread_local! {
+    /// The day this thread is running, if it's inside [`for_day`].
+    static CURRENT: Cell<Option<DayId>> = const { Cell::new(None) };
+}
+
+pub fn set_days(days: Vec<DayId>) {
+    *DAYS.write().unwrap() = days;
+}
+
+/// Runs `f` as part of `day`, so its messages are only written if that day's are wanted.
+/// Messages from other threads `f` starts, like those of a parallel loop, are left out when only
+/// some days are wanted.
+pub fn for_day<T>(day: DayId, f: impl FnOnce() -> T) -> T {
+    let outer = CURRENT.replace(Some(day));
+    let result = f();
+    CURRENT.set(outer);
+    result
+}
+
+/// Whether messages at `level` are being written from this thread.
+pub fn enabled(level: Level) -> bool {
+    if level == Level::Off || level > self::level() {
+        return false;
+    }
+    let days = DAYS.read().unwrap();
+    days.is_empty() || CURRENT.get().is_some_and(|day| days.contains(&day))
+}
+
+/// Writes the message made by `f` to stderr, with each line marked with `level`, if that level
+/// is on.
+pub fn log<D: Display>(level: Level, f: impl FnOnce() -> D) {
+    if !enabled(level) {
+        return;
+    }
+    let message = f().to_string();
+    // One lock for the whole message keeps it together when days run in parallel
+    let mut stderr = std::io::stderr().lock();
+    for line in message.lines() {
+        let _ = writeln!(stderr, "[{}] {line}", level.name());
+    }
+}
+
+pub fn info<D: Display>(f: impl FnOnce() -> D) {
+    log(Level::Info, f)
+}
+
+pub fn debug<D: Display>(f: impl FnOnce() -> D) {
+    log(Level::Debug, f)
+}
+
+pub fn trace<D: Display>(f: impl FnOnce() -> D) {
+    log(Level::Trace, f)
+}
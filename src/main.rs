@@ -1,37 +1,995 @@
-mod days;
+use aoc23::answers::{self, Expected};
+use aoc23::days::{DayId, GenerateFn, ParseFn, CROSS_CHECKED, DAYS, DEFAULT_YEAR, GENERATORS};
+use aoc23::generate::Rng;
+use aoc23::inputs::{self, Source, INPUTS_DIR, INPUTS_ENV};
+use aoc23::json::Json;
+use aoc23::log::{self, Level};
+use aoc23::render::{self, ImageFormat, Palette, PALETTES};
+use aoc23::runner::{self, Answers, ParseFailure, Parts, Report, Stats};
+use aoc23::scaffold;
+use aoc23::trace::{self, Trace};
 
-use days::DAYS;
-
-use clap::Parser;
-use std::path::Path;
+use clap::{Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
 
 #[derive(Parser, Debug)]
+#[command(
+    subcommand_negates_reqs = true,
+    override_usage = "aoc23 [OPTIONS] [YEAR] <DAY> <INPUT>\n       aoc23 [OPTIONS] <COMMAND>"
+)]
 struct Args {
-    name: String,
-    input: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The day to run, with its year first unless it's 2023, then its input: a variant under the
+    /// inputs directory, a path to an input file, or `-` for stdin. Like `2023 7 real` or
+    /// `day7 real`.
+    #[arg(required = true, num_args = 2..=3, value_names = ["YEAR", "DAY", "INPUT"])]
+    run: Vec<String>,
+
+    /// The year of the days named without one, and the only year to run, verify or check when no
+    /// day is named. Defaults to 2023 for naming days, and every year otherwise.
+    #[arg(long, global = true)]
+    year: Option<u32>,
+
+    /// Directory holding the inputs, as `<year>/<day>/<variant>.txt`.
+    #[arg(long, global = true, env = INPUTS_ENV, default_value = INPUTS_DIR)]
+    inputs: PathBuf,
+
+    /// Run each day this many times and report min, median and max timings.
+    #[arg(long, global = true, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    repeat: u32,
+
+    /// Run only this part of each day, leaving the other out of the answers and timings.
+    #[arg(long, global = true, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Write what the days are up to on stderr. Repeat for more detail, up to `-vvv`.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only write what this day is up to, like `--log day17`, turning on `-v` if it isn't already.
+    /// Give it more than once for more days.
+    #[arg(long, global = true, value_name = "DAY")]
+    log: Vec<String>,
+
+    #[command(flatten)]
+    trace: TraceArgs,
 }
 
-fn main() -> Result<(), String> {
-    let args = Args::parse();
+#[derive(clap::Args, Debug)]
+struct TraceArgs {
+    /// Record each step of the days that simulate something, and play it back in the terminal
+    /// (`-`) or write it to a directory with a picture and text file per step.
+    #[arg(long, value_name = "DIR")]
+    trace: Option<String>,
+
+    /// Stop recording after this many steps.
+    #[arg(long, default_value_t = 1000)]
+    trace_limit: usize,
+
+    /// Milliseconds to show each step for when playing a trace back in the terminal.
+    #[arg(long, default_value_t = 50)]
+    trace_delay: u64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Answers and timings for people to read.
+    Text,
+    /// One JSON record, or an array of one record per day for `all`.
+    Json,
+    /// One JSON record per line, written as each day finishes, so in the order they finish with
+    /// `--parallel`.
+    Ndjson,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run every day against the same input variant.
+    All {
+        #[arg(default_value = "real")]
+        input: String,
+        /// Run the days at the same time. Their timings then include waiting on each other.
+        #[arg(long)]
+        parallel: bool,
+    },
+    /// Check every input with a recorded `.answers` file, optionally for a single day.
+    Verify { name: Option<String> },
+    /// Draw a day's puzzle as an image, in the format named by the output's extension.
+    Render {
+        name: String,
+        /// A variant under the inputs directory, a path to an input file, or `-` for stdin.
+        input: String,
+        /// Where to write the `.ppm`, `.png` or `.svg` image.
+        output: PathBuf,
+        #[arg(long, default_value = "dark")]
+        palette: String,
+        /// Size in pixels of each cell of the puzzle.
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+        scale: u32,
+    },
+    /// Write a made up input for a day to stdout, the same one every time for the same seed.
+    Generate {
+        name: String,
+        /// How big to make it, in whatever the day's input is made of, like lines, rows or
+        /// bricks. Defaults to about the size of the real input.
+        #[arg(long)]
+        size: Option<usize>,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Check the days' shortcuts against brute force on small generated inputs, for every day
+    /// with reference solvers or just one of them.
+    Differential {
+        name: Option<String>,
+        /// Size of input to generate, instead of the day's own small size.
+        #[arg(long)]
+        size: Option<usize>,
+        /// How many inputs to check, generated from the seeds counting up from 0.
+        #[arg(long, default_value_t = 10)]
+        seeds: u64,
+    },
+    /// Start a new day, like `day26` or `2024/day1`, with a module to fill in, empty example and
+    /// real inputs, and answers for the example to fill in. They all go in the crate the runner
+    /// was built from, with a relative `--inputs` taken from there.
+    New { name: String },
+    /// Re-run a day against every one of its inputs whenever one changes, showing the answers next
+    /// to the recorded ones, and rebuild and restart when the day's module changes. Stop with
+    /// Ctrl-C.
+    Watch {
+        name: String,
+        /// A variant or path to an input file to watch, instead of the day's whole inputs folder.
+        input: Option<String>,
+        /// Milliseconds between checks for changes.
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+    },
+}
+
+/// The day that `name` stands for, like `7`, `day7` or `2024/day7`, taking it to be in `year`, or
+/// 2023, unless it says.
+fn day_id(year: Option<u32>, name: &str) -> Result<DayId, String> {
+    DayId::parse(name, year.unwrap_or(DEFAULT_YEAR))
+        .ok_or_else(|| format!("Expected a day like 7, day7 or 2023/day7, not {name}"))
+}
+
+fn find_day(day: DayId) -> Result<ParseFn, String> {
+    for (id, parse) in DAYS {
+        if *id == day {
+            return Ok(*parse);
+        }
+    }
+    Err(format!("Unknown day {day}"))
+}
+
+fn fmt_stats(stats: Stats, runs: usize) -> String {
+    if runs == 1 {
+        format!("{:.2?}", stats.median)
+    } else {
+        format!(
+            "{:.2?} median, {:.2?} min, {:.2?} max over {runs} runs",
+            stats.median, stats.min, stats.max
+        )
+    }
+}
+
+/// A parse failure with the offending line shown under it.
+fn describe(failure: &ParseFailure) -> String {
+    match failure {
+        ParseFailure::Error(e) if e.line > 0 => format!("{e}\n{}", e.snippet()),
+        other => other.to_string(),
+    }
+}
+
+/// Why a day produced no answers at all.
+enum Failure {
+    Read(String),
+    Parse(ParseFailure),
+}
+
+/// The result of running one day against one input.
+struct Outcome {
+    day: DayId,
+    source: Source,
+    result: Result<Report, Failure>,
+}
+
+impl Outcome {
+    fn is_ok(&self) -> bool {
+        matches!(&self.result, Ok(report) if report.answers.is_ok())
+    }
+
+    /// Whether the day ran and failed, as opposed to having no input to run against.
+    fn failed(&self) -> bool {
+        match &self.result {
+            Ok(report) => !report.answers.is_ok(),
+            Err(Failure::Parse(_)) => true,
+            Err(Failure::Read(_)) => false,
+        }
+    }
+}
+
+fn run_day(day: DayId, parse: ParseFn, source: Source, parts: Parts, repeat: usize) -> Outcome {
+    let result = match source.read() {
+        Ok(input) => log::for_day(day, || runner::run_repeated(parse, &input, parts, repeat))
+            .map_err(|e| Failure::Parse(e.in_file(&day.to_string(), source.path()))),
+        Err(e) => Err(Failure::Read(e)),
+    };
+    Outcome {
+        day,
+        source,
+        result,
+    }
+}
+
+fn stats_json(stats: Stats) -> Json {
+    Json::object([
+        ("min_ns", stats.min.as_nanos().into()),
+        ("median_ns", stats.median.as_nanos().into()),
+        ("max_ns", stats.max.as_nanos().into()),
+    ])
+}
+
+fn part_json(part: &Option<Result<String, String>>) -> Json {
+    let (answer, error) = match part {
+        None => return Json::Null,
+        Some(Ok(answer)) => (Json::from(answer.as_str()), Json::Null),
+        Some(Err(e)) => (Json::Null, Json::from(e.as_str())),
+    };
+    Json::object([("answer", answer), ("error", error)])
+}
+
+/// The timings of a part, or nothing if it wasn't run.
+fn part_stats_json<T>(part: &Option<T>, stats: Stats) -> Json {
+    match part {
+        Some(_) => stats_json(stats),
+        None => Json::Null,
+    }
+}
+
+fn failure_json(failure: &Failure) -> Json {
+    match failure {
+        Failure::Read(msg) => {
+            Json::object([("kind", "read".into()), ("message", msg.as_str().into())])
+        }
+        Failure::Parse(ParseFailure::Panic(msg)) => {
+            Json::object([("kind", "panic".into()), ("message", msg.as_str().into())])
+        }
+        Failure::Parse(ParseFailure::Error(e)) => Json::object([
+            ("kind", "parse".into()),
+            ("message", e.to_string().into()),
+            ("line", e.line.into()),
+            ("column", e.column.into()),
+            ("expected", e.expected.as_str().into()),
+            ("found", e.found.as_str().into()),
+        ]),
+    }
+}
+
+fn outcome_json(outcome: &Outcome) -> Json {
+    let (part1, part2, timings, error) = match &outcome.result {
+        Ok(report) => (
+            part_json(&report.answers.part1),
+            part_json(&report.answers.part2),
+            Json::object([
+                ("runs", report.timings.len().into()),
+                ("parse", stats_json(report.parse_stats())),
+                (
+                    "part1",
+                    part_stats_json(&report.answers.part1, report.part1_stats()),
+                ),
+                (
+                    "part2",
+                    part_stats_json(&report.answers.part2, report.part2_stats()),
+                ),
+            ]),
+            Json::Null,
+        ),
+        Err(failure) => (Json::Null, Json::Null, Json::Null, failure_json(failure)),
+    };
+    Json::object([
+        ("year", (outcome.day.year as usize).into()),
+        ("day", outcome.day.name().into()),
+        ("input", outcome.source.to_string().into()),
+        ("ok", outcome.is_ok().into()),
+        ("part1", part1),
+        ("part2", part2),
+        ("timings", timings),
+        ("error", error),
+    ])
+}
+
+fn print_outcome(outcome: &Outcome) -> Result<(), String> {
+    let report = match &outcome.result {
+        Ok(report) => report,
+        Err(Failure::Read(e)) => return Err(e.clone()),
+        Err(Failure::Parse(e)) => return Err(describe(e)),
+    };
+    let Answers { part1, part2 } = &report.answers;
+    let show = |r: &Result<String, String>| match r {
+        Ok(a) => a.clone(),
+        Err(e) => format!("failed: {e}"),
+    };
+
+    if let Some(part1) = part1 {
+        println!("First: {}", show(part1));
+    }
+    if let Some(part2) = part2 {
+        println!("Second: {}", show(part2));
+    }
+
+    let runs = report.timings.len();
+    println!("Parse: {}", fmt_stats(report.parse_stats(), runs));
+    if part1.is_some() {
+        println!("Part 1: {}", fmt_stats(report.part1_stats(), runs));
+    }
+    if part2.is_some() {
+        println!("Part 2: {}", fmt_stats(report.part2_stats(), runs));
+    }
+    Ok(())
+}
+
+const TRACE_SCALE: usize = 4;
+
+/// Plays `trace` back on stderr, out of the way of the answers, or writes it under `dest`.
+fn show_trace(trace: &Trace, dest: &str, delay: Duration) -> Result<(), String> {
+    if dest == "-" {
+        for (i, frame) in trace.frames.iter().enumerate() {
+            let n = trace.frames.len();
+            eprint!(
+                "\x1b[2J\x1b[H{} ({}/{n})\n{}",
+                frame.label,
+                i + 1,
+                frame.text
+            );
+            std::thread::sleep(delay);
+        }
+    } else {
+        let dir = Path::new(dest);
+        let write = |path: PathBuf, contents: &[u8]| {
+            std::fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))
+        };
+        std::fs::create_dir_all(dir).map_err(|e| format!("{dest}: {e}"))?;
+        for (i, frame) in trace.frames.iter().enumerate() {
+            let text = format!("{}\n{}", frame.label, frame.text);
+            write(dir.join(format!("{i:05}.txt")), text.as_bytes())?;
+            if let Some(picture) = &frame.picture {
+                let png = render::encode(picture, TRACE_SCALE, ImageFormat::Png);
+                write(dir.join(format!("{i:05}.png")), &png)?;
+            }
+        }
+        eprintln!("Wrote {} steps to {dest}", trace.frames.len());
+    }
+    if trace.dropped > 0 {
+        eprintln!("Left out {} steps past the limit", trace.dropped);
+    }
+    Ok(())
+}
+
+fn run_one(
+    root: &Path,
+    year: Option<u32>,
+    run: &[String],
+    parts: Parts,
+    repeat: usize,
+    format: Format,
+    trace_args: &TraceArgs,
+) -> Result<(), String> {
+    let (year, name, input) = match run {
+        [year, name, input] => {
+            let year = year
+                .parse::<u32>()
+                .map_err(|_| format!("Expected a year like 2023, not {year}"))?;
+            (Some(year), name, input)
+        }
+        [name, input] => (year, name, input),
+        _ => unreachable!("clap takes two or three arguments"),
+    };
+    let day = day_id(year, name)?;
+    let f = find_day(day)?;
+
+    let source = Source::resolve(root, day, input);
+    let outcome = if trace_args.trace.is_some() {
+        if repeat > 1 {
+            return Err("--trace records a single run, so can't be used with --repeat".to_owned());
+        }
+        // Keep the steps in order by running the day's parallel loops one item at a time
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .map_err(|e| e.to_string())?;
+        trace::start(&PALETTES[0], trace_args.trace_limit);
+        pool.install(|| run_day(day, f, source, parts, repeat))
+    } else {
+        run_day(day, f, source, parts, repeat)
+    };
+    if let (Some(dest), Some(recorded)) = (&trace_args.trace, trace::finish()) {
+        if recorded.frames.is_empty() {
+            eprintln!("{day} has no steps to trace");
+        } else {
+            let delay = Duration::from_millis(trace_args.trace_delay);
+            show_trace(&recorded, dest, delay)?;
+        }
+    }
+    match format {
+        Format::Text => print_outcome(&outcome)?,
+        Format::Json | Format::Ndjson => println!("{}", outcome_json(&outcome)),
+    }
+
+    if outcome.is_ok() {
+        Ok(())
+    } else {
+        Err(format!("{day} failed"))
+    }
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths = header.iter().map(|h| h.len()).collect::<Vec<_>>();
+    for row in rows.iter() {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.len());
+        }
+    }
+    let header = header.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let mut line = String::new();
+        for (i, (cell, w)) in row.iter().zip(widths.iter()).enumerate() {
+            if i == 0 {
+                line += &format!("{cell:<w$}");
+            } else if i == row.len() - 1 {
+                line += &format!("  {cell}");
+            } else {
+                line += &format!("  {cell:>w$}");
+            }
+        }
+        println!("{line}");
+    }
+}
+
+fn table_row(outcome: &Outcome) -> Vec<String> {
+    let mut row = vec![outcome.day.to_string()];
+    match &outcome.result {
+        Ok(report) => {
+            let Answers { part1, part2 } = &report.answers;
+            let status = match [part1, part2]
+                .into_iter()
+                .flatten()
+                .find_map(|p| p.as_ref().err())
+            {
+                None => "ok".to_owned(),
+                Some(e) => format!("failed: {e}"),
+            };
+            let show = |r: &Option<Result<String, String>>| match r {
+                Some(Ok(answer)) => answer.clone(),
+                _ => "-".to_owned(),
+            };
+            let time = |s: Stats| format!("{:.2?}", s.median);
+            let part_time = |r: &Option<Result<String, String>>, s: Stats| match r {
+                Some(_) => time(s),
+                None => "-".to_owned(),
+            };
+            row.extend([
+                show(part1),
+                show(part2),
+                time(report.parse_stats()),
+                part_time(part1, report.part1_stats()),
+                part_time(part2, report.part2_stats()),
+                status,
+            ]);
+        }
+        Err(failure) => {
+            row.extend(["-"; 5].map(|s| s.to_owned()));
+            row.push(match failure {
+                Failure::Read(_) => format!("no input at {}", outcome.source),
+                Failure::Parse(e) => format!("failed: {e}"),
+            });
+        }
+    }
+    row
+}
+
+fn run_all(
+    root: &Path,
+    year: Option<u32>,
+    input: &str,
+    parts: Parts,
+    repeat: usize,
+    format: Format,
+    parallel: bool,
+) -> Result<(), String> {
+    if !inputs::is_variant(input) {
+        return Err(format!(
+            "all runs every day against a variant name, not {input}"
+        ));
+    }
+    let days = DAYS
+        .iter()
+        .filter(|(day, _)| year.is_none_or(|year| year == day.year))
+        .collect::<Vec<_>>();
+    if days.is_empty() {
+        return Err(format!("No days from {}", year.unwrap()));
+    }
+    let run = |(day, parse): &&(DayId, ParseFn)| {
+        let source = Source::File(inputs::input_path(root, *day, input));
+        run_day(*day, *parse, source, parts, repeat)
+    };
+    // Each record goes out as soon as its day is done, in the order they finish in parallel
+    let stream = |outcome: &Outcome| {
+        if format == Format::Ndjson {
+            println!("{}", outcome_json(outcome));
+        }
+    };
+    let outcomes = if parallel {
+        days.par_iter().map(run).inspect(stream).collect::<Vec<_>>()
+    } else {
+        days.iter().map(run).inspect(stream).collect::<Vec<_>>()
+    };
+
+    match format {
+        Format::Text => print_table(
+            &[
+                "Day", "Part 1", "Part 2", "Parse", "Time 1", "Time 2", "Status",
+            ],
+            &outcomes.iter().map(table_row).collect::<Vec<_>>(),
+        ),
+        Format::Json => println!(
+            "{}",
+            Json::Array(outcomes.iter().map(outcome_json).collect())
+        ),
+        Format::Ndjson => {}
+    }
+
+    let failed = outcomes.iter().filter(|o| o.failed()).count();
+    if failed > 0 {
+        Err(format!("{failed} day(s) failed"))
+    } else {
+        Ok(())
+    }
+}
 
-    let mut f: Option<&fn(&str) -> (String, String)> = None;
+/// Whether `day` is the one named, or from `year` if none is.
+fn selected(day: DayId, year: Option<u32>, only: Option<DayId>) -> bool {
+    match only {
+        Some(only) => day == only,
+        None => year.is_none_or(|year| year == day.year),
+    }
+}
 
-    for day in DAYS {
-        if day.0 == args.name {
-            f = Some(&day.1);
-            break;
+fn verify(root: &Path, year: Option<u32>, only: Option<&str>, parts: Parts) -> Result<(), String> {
+    let only = only.map(|name| day_id(year, name)).transpose()?;
+    if let Some(day) = only {
+        find_day(day)?;
+    }
+    let (mut passed, mut failed) = (0usize, 0usize);
+    for (day, parse) in DAYS {
+        if !selected(*day, year, only) {
+            continue;
+        }
+        for variant in inputs::variants(root, *day) {
+            let path = inputs::input_path(root, *day, &variant);
+            let Some(verdict) = log::for_day(*day, || answers::verify(*day, *parse, &path, parts))?
+            else {
+                continue;
+            };
+            let elapsed = verdict
+                .elapsed
+                .map(|e| format!(" ({e:.2?})"))
+                .unwrap_or_default();
+            if verdict.passed() {
+                passed += 1;
+                println!("{day}/{variant}: ok{elapsed}");
+            } else {
+                failed += 1;
+                println!("{day}/{variant}: FAILED{elapsed}");
+                for problem in verdict.problems {
+                    println!("    {problem}");
+                }
+            }
         }
     }
 
-    let f = f.ok_or(format!("Unknown name {}", args.name))?;
+    println!("{passed} passed, {failed} failed");
+    if failed > 0 {
+        Err(format!("{failed} input(s) failed verification"))
+    } else {
+        Ok(())
+    }
+}
 
-    let path_name = format!("./inputs/{}/{}.txt", args.name, args.input);
-    let ipath = Path::new(&path_name);
-    let input = std::fs::read_to_string(ipath).unwrap();
+fn render(
+    root: &Path,
+    year: Option<u32>,
+    name: &str,
+    input: &str,
+    output: &Path,
+    palette: &str,
+    scale: usize,
+) -> Result<(), String> {
+    let day = day_id(year, name)?;
+    let parse = find_day(day)?;
+    let palette = Palette::named(palette).ok_or_else(|| {
+        let names = PALETTES.iter().map(|p| p.name).collect::<Vec<_>>();
+        format!(
+            "Unknown palette {palette}, expected one of {}",
+            names.join(", ")
+        )
+    })?;
+    let format = ImageFormat::of_path(output)
+        .ok_or_else(|| format!("{} should end in .ppm, .png or .svg", output.display()))?;
 
-    let (a, b) = f(&input);
+    let source = Source::resolve(root, day, input);
+    let parsed = parse(&source.read()?).map_err(|e| {
+        describe(&ParseFailure::Error(
+            e.in_file(&day.to_string(), source.path()),
+        ))
+    })?;
+    let image = parsed
+        .render(palette)
+        .ok_or_else(|| format!("{day} has nothing to draw"))?;
 
-    println!("First: {a}\nSecond: {b}");
+    std::fs::write(output, render::encode(&image, scale, format))
+        .map_err(|e| format!("{}: {e}", output.display()))?;
+    println!(
+        "Wrote {}x{} cells to {}",
+        image.width(),
+        image.height(),
+        output.display()
+    );
+    Ok(())
+}
 
+fn find_generator(day: DayId) -> Result<GenerateFn, String> {
+    GENERATORS
+        .iter()
+        .find(|(id, _)| *id == day)
+        .map(|(_, f)| *f)
+        .ok_or_else(|| format!("Unknown day {day}"))
+}
+
+fn generate(year: Option<u32>, name: &str, size: Option<usize>, seed: u64) -> Result<(), String> {
+    let f = find_generator(day_id(year, name)?)?;
+    print!("{}", f(&mut Rng::new(seed), size));
     Ok(())
 }
+
+fn differential(
+    year: Option<u32>,
+    only: Option<&str>,
+    size: Option<usize>,
+    seeds: u64,
+) -> Result<(), String> {
+    let only = only.map(|name| day_id(year, name)).transpose()?;
+    if let Some(day) = only {
+        if !CROSS_CHECKED.iter().any(|(id, _)| *id == day) {
+            let names = CROSS_CHECKED
+                .iter()
+                .map(|(id, _)| id.to_string())
+                .collect::<Vec<_>>();
+            return Err(format!(
+                "{day} has no reference solver, only {} do",
+                names.join(", ")
+            ));
+        }
+    }
+
+    let mut disagreed = 0usize;
+    for (name, small_size) in CROSS_CHECKED {
+        if !selected(*name, year, only) {
+            continue;
+        }
+        let parse = find_day(*name)?;
+        let generate = find_generator(*name)?;
+        let size = size.unwrap_or(*small_size);
+
+        let checks = log::for_day(*name, || {
+            runner::cross_check_seeds(parse, generate, size, seeds)
+        });
+        for d in &checks.disagreements {
+            println!("{name} seed {}: {}", d.seed, d.what);
+            println!(
+                "    input from `aoc23 generate {name} --size {size} --seed {}`",
+                d.seed
+            );
+        }
+        println!(
+            "{name}: {} answers compared over {seeds} inputs, {} disagreed, {} too slow to check",
+            checks.compared,
+            checks.disagreements.len(),
+            checks.skipped
+        );
+        disagreed += checks.disagreements.len();
+    }
+
+    if disagreed > 0 {
+        Err(format!("{disagreed} answer(s) disagreed"))
+    } else {
+        Ok(())
+    }
+}
+
+/// The crate the runner was built from, which `new` adds days to.
+fn crate_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn sources() -> PathBuf {
+    crate_root().join("src/days")
+}
+
+fn new(root: &Path, year: Option<u32>, name: &str) -> Result<(), String> {
+    let day = day_id(year, name)?;
+    if find_day(day).is_ok() {
+        return Err(format!("{day} already exists"));
+    }
+    // The inputs go in the same crate as the module, wherever this is run from
+    let inputs = crate_root().join(root.strip_prefix(".").unwrap_or(root));
+    for path in scaffold::new_day(&sources(), &inputs, day)? {
+        println!("Created {}", path.display());
+    }
+    Ok(())
+}
+
+/// When each of `paths` was last modified, or `None` for those that don't exist.
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// The files in `dir`, sorted by name, so files coming and going count as changes too.
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files = std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// A row of the watch table for `input`, with its answers next to the recorded ones.
+fn watch_row(label: String, parse: ParseFn, input: &Path, parts: Parts) -> Vec<String> {
+    let expected = Expected::load(input);
+    let result = std::fs::read_to_string(input)
+        .map_err(|e| format!("{}: {e}", input.display()))
+        .and_then(|contents| runner::run(parse, &contents, parts).map_err(|e| e.to_string()));
+    let (answers, timings) = match result {
+        Ok(run) => run,
+        Err(e) => return vec![label, "-".into(), "-".into(), "-".into(), "-".into(), e],
+    };
+    let (expected, status) = match expected {
+        Ok(Some(expected)) => {
+            let mismatches = expected.check(&answers);
+            let status = if !answers.is_ok() {
+                "failed"
+            } else if !mismatches.is_empty() {
+                "WRONG"
+            } else {
+                "ok"
+            };
+            (expected, status.to_owned())
+        }
+        Ok(None) => (Expected::default(), "no answers recorded".to_owned()),
+        Err(e) => (Expected::default(), e),
+    };
+    let show = |r: &Option<Result<String, String>>| match r {
+        Some(Ok(answer)) => answer.clone(),
+        Some(Err(e)) => format!("failed: {e}"),
+        None => "-".to_owned(),
+    };
+    let elapsed = timings.parse + timings.part1 + timings.part2;
+    vec![
+        label,
+        show(&answers.part1),
+        expected.part1.unwrap_or_else(|| "-".to_owned()),
+        show(&answers.part2),
+        expected.part2.unwrap_or_else(|| "-".to_owned()),
+        format!("{status} ({elapsed:.2?})"),
+    ]
+}
+
+/// Rebuilds this binary with the profile it was built with, into the target directory it's in, and
+/// replaces this process with the new build given the same arguments. Returns why if it couldn't.
+fn rebuild_and_restart() -> String {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return format!("Couldn't find this binary: {e}"),
+    };
+    let (Some(profile_dir), Some(target_dir)) = (exe.parent(), exe.parent().and_then(Path::parent))
+    else {
+        return format!("{} isn't in a target directory", exe.display());
+    };
+    let profile = match profile_dir.file_name().and_then(|p| p.to_str()) {
+        Some("debug") => "dev",
+        Some(profile) => profile,
+        None => return format!("{} isn't in a target directory", exe.display()),
+    };
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let built = std::process::Command::new(option_env!("CARGO").unwrap_or("cargo"))
+        .args(["build", "--bin", "aoc23", "--profile", profile])
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .arg("--target-dir")
+        .arg(target_dir)
+        .status();
+    match built {
+        Ok(status) if status.success() => {}
+        Ok(status) => return format!("The build failed ({status})"),
+        Err(e) => return format!("Couldn't run cargo: {e}"),
+    }
+
+    let mut restart = std::process::Command::new(&exe);
+    restart.args(std::env::args_os().skip(1));
+    restart_as(restart)
+}
+
+#[cfg(unix)]
+fn restart_as(mut command: std::process::Command) -> String {
+    use std::os::unix::process::CommandExt;
+    format!("Couldn't restart: {}", command.exec())
+}
+
+#[cfg(not(unix))]
+fn restart_as(mut command: std::process::Command) -> String {
+    match command.status() {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => format!("Couldn't restart: {e}"),
+    }
+}
+
+fn watch(
+    root: &Path,
+    year: Option<u32>,
+    name: &str,
+    input: Option<&str>,
+    parts: Parts,
+    interval: Duration,
+) -> Result<(), String> {
+    let day = day_id(year, name)?;
+    let parse = find_day(day)?;
+    let dir = inputs::day_dir(root, day);
+    let file = match input.map(|input| Source::resolve(root, day, input)) {
+        Some(Source::Stdin) => return Err("watch needs an input file, not stdin".to_owned()),
+        Some(Source::File(path)) => Some(path),
+        None => None,
+    };
+    let watched = || match &file {
+        Some(path) => vec![path.clone(), answers::answers_path(path)],
+        None => files_in(&dir),
+    };
+    let source = scaffold::source_path(&sources(), day);
+
+    let clear = std::io::stdout().is_terminal();
+    let mut last = None;
+    let mut last_source = modified(std::slice::from_ref(&source));
+    loop {
+        let paths = watched();
+        let now = (paths.clone(), modified(&paths));
+        if last.as_ref() != Some(&now) {
+            last = Some(now);
+            let rows = log::for_day(day, || {
+                let mut rows = inputs::variants(root, day)
+                    .into_iter()
+                    .map(|v| watch_row(v.clone(), parse, &inputs::input_path(root, day, &v), parts))
+                    .collect::<Vec<_>>();
+                if let Some(path) = file.as_ref().filter(|p| p.parent() != Some(dir.as_path())) {
+                    rows.push(watch_row(path.display().to_string(), parse, path, parts));
+                }
+                rows
+            });
+            if clear {
+                print!("\x1b[2J\x1b[H");
+            }
+            println!(
+                "{day}, watching {}",
+                file.as_ref().unwrap_or(&dir).display()
+            );
+            print_table(
+                &[
+                    "Input", "Part 1", "Expected", "Part 2", "Expected", "Status",
+                ],
+                &rows,
+            );
+        }
+
+        let source_now = modified(std::slice::from_ref(&source));
+        if source_now != last_source {
+            last_source = source_now;
+            println!("{} changed, rebuilding", source.display());
+            // Only comes back if the build failed, in which case cargo has said why
+            let e = rebuild_and_restart();
+            println!("{e}, still running the last build");
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let year = args.year;
+
+    let log_days = match args.log.iter().map(|name| day_id(year, name)).collect() {
+        Ok(days) => days,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    // Naming days to log is asking for their messages
+    let verbosity = if args.log.is_empty() {
+        args.verbose
+    } else {
+        args.verbose.max(1)
+    };
+    log::set_level(Level::from_verbosity(verbosity));
+    log::set_days(log_days);
+
+    let parts = Parts::only(args.part);
+    let result = match args.command {
+        Some(Command::All { input, parallel }) => run_all(
+            &args.inputs,
+            year,
+            &input,
+            parts,
+            args.repeat as usize,
+            args.format,
+            parallel,
+        ),
+        Some(Command::Verify { name }) => verify(&args.inputs, year, name.as_deref(), parts),
+        Some(Command::Render {
+            name,
+            input,
+            output,
+            palette,
+            scale,
+        }) => render(
+            &args.inputs,
+            year,
+            &name,
+            &input,
+            &output,
+            &palette,
+            scale as usize,
+        ),
+        Some(Command::Generate { name, size, seed }) => generate(year, &name, size, seed),
+        Some(Command::Differential { name, size, seeds }) => {
+            differential(year, name.as_deref(), size, seeds)
+        }
+        Some(Command::New { name }) => new(&args.inputs, year, &name),
+        Some(Command::Watch {
+            name,
+            input,
+            interval,
+        }) => watch(
+            &args.inputs,
+            year,
+            &name,
+            input.as_deref(),
+            parts,
+            Duration::from_millis(interval),
+        ),
+        None => run_one(
+            &args.inputs,
+            year,
+            &args.run,
+            parts,
+            args.repeat as usize,
+            args.format,
+            &args.trace,
+        ),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
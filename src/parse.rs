@@ -0,0 +1,230 @@
+//! Parsing helpers for puzzle input, with errors located to the line and column they were found
+//! at.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A parser's complaint about its input: where it was, what it wanted and what it got instead.
+///
+/// Parsers only know the input text, so `origin` is filled in by whoever read the file, with
+/// [`ParseError::in_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The day and file the input came from.
+    pub origin: Option<Box<(String, PathBuf)>>,
+    /// 1-based, or 0 if the error is about the input as a whole.
+    pub line: usize,
+    /// 1-based, counted in characters, or 0 if the error is about the input as a whole.
+    pub column: usize,
+    /// The offending line, for showing the error in context.
+    pub text: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    /// An error about the input as a whole rather than any one place in it.
+    pub fn whole_input(expected: impl Display, found: impl Display) -> ParseError {
+        ParseError {
+            origin: None,
+            line: 0,
+            column: 0,
+            text: String::new(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+
+    /// An error for input that stopped before the parser had everything it needed.
+    pub fn end_of_input(input: &str, expected: impl Display) -> ParseError {
+        ParseError {
+            origin: None,
+            line: input.lines().count() + 1,
+            column: 1,
+            text: String::new(),
+            expected: expected.to_string(),
+            found: "end of input".to_owned(),
+        }
+    }
+
+    pub fn in_file(mut self, day: &str, file: &Path) -> ParseError {
+        self.origin = Some(Box::new((day.to_owned(), file.to_owned())));
+        self
+    }
+
+    /// The offending line with a caret under the column, or nothing for whole-input errors.
+    pub fn snippet(&self) -> String {
+        if self.line == 0 {
+            return String::new();
+        }
+        let num = self.line.to_string();
+        format!(
+            "{num} | {}\n{:w$} | {:>c$}",
+            self.text,
+            "",
+            "^",
+            w = num.len(),
+            c = self.column
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.origin.as_deref(), self.line) {
+            (Some((day, file)), 0) => write!(f, "{day}: {}: ", file.display())?,
+            (Some((day, file)), line) => {
+                write!(f, "{day}: {}:{line}:{}: ", file.display(), self.column)?
+            }
+            (None, 0) => {}
+            (None, line) => write!(f, "line {line}, column {}: ", self.column)?,
+        }
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A line of input, numbered from 1, that errors can be located within.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub num: usize,
+    pub text: &'a str,
+}
+
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| Line { num: i + 1, text })
+}
+
+/// The runs of lines separated by blank lines, leaving out any runs with nothing in them.
+pub fn sections(input: &str) -> Vec<Vec<Line<'_>>> {
+    let mut sections = vec![Vec::<Line>::new()];
+    for line in lines(input) {
+        if line.text.trim().is_empty() {
+            sections.push(Vec::new());
+        } else {
+            sections.last_mut().unwrap().push(line);
+        }
+    }
+    sections.retain(|s| !s.is_empty());
+    sections
+}
+
+impl<'a> Line<'a> {
+    /// The column `part` starts at. `part` must be a slice of this line's text.
+    pub fn column_of(&self, part: &str) -> usize {
+        let offset = part.as_ptr() as usize - self.text.as_ptr() as usize;
+        assert!(offset <= self.text.len(), "not part of this line");
+        self.text[..offset].chars().count() + 1
+    }
+
+    pub fn error(&self, column: usize, expected: impl Display, found: impl Display) -> ParseError {
+        ParseError {
+            origin: None,
+            line: self.num,
+            column,
+            text: self.text.to_owned(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+
+    /// An error pointing at `part`, a slice of this line, as what was found instead.
+    pub fn error_at(&self, part: &str, expected: impl Display) -> ParseError {
+        let found = if part.is_empty() {
+            "nothing".to_owned()
+        } else {
+            format!("`{part}`")
+        };
+        self.error(self.column_of(part), expected, found)
+    }
+
+    /// An error pointing at the character `c`, found at character index `idx` of this line.
+    pub fn char_error(&self, idx: usize, c: char, expected: impl Display) -> ParseError {
+        self.error(idx + 1, expected, format!("{c:?}"))
+    }
+
+    /// An error for a line that ended before `expected`.
+    pub fn end_error(&self, expected: impl Display) -> ParseError {
+        self.error(self.text.chars().count() + 1, expected, "end of line")
+    }
+
+    /// Parses `part`, a slice of this line.
+    pub fn parse<T: FromStr>(&self, part: &str, expected: impl Display) -> Result<T, ParseError> {
+        part.parse::<T>().map_err(|_| self.error_at(part, expected))
+    }
+
+    /// Splits `part`, a slice of this line, around the first `sep`.
+    pub fn split_once(
+        &self,
+        part: &'a str,
+        sep: &str,
+        expected: impl Display,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        part.split_once(sep)
+            .ok_or_else(|| self.error_at(part, expected))
+    }
+
+    /// `part`, a slice of this line, without `prefix`.
+    pub fn strip_prefix(&self, part: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+        part.strip_prefix(prefix)
+            .ok_or_else(|| self.error_at(part, format!("`{prefix}`")))
+    }
+
+    /// `part`, a slice of this line, without `suffix`.
+    pub fn strip_suffix(&self, part: &'a str, suffix: &str) -> Result<&'a str, ParseError> {
+        part.strip_suffix(suffix)
+            .ok_or_else(|| self.error_at(part, format!("something ending `{suffix}`")))
+    }
+
+    /// Splits a `key: values` line into its key and values, trimmed of whitespace.
+    pub fn key_values(&self, expected: impl Display) -> Result<(&'a str, &'a str), ParseError> {
+        let (key, values) = self.split_once(self.text, ":", expected)?;
+        Ok((key.trim(), values.trim()))
+    }
+
+    /// Parses each whitespace separated item of `part`, a slice of this line. Whether negative
+    /// numbers are allowed is down to `T`.
+    pub fn numbers<T: FromStr>(
+        &self,
+        part: &'a str,
+        expected: impl Display,
+    ) -> Result<Vec<T>, ParseError> {
+        part.split_whitespace()
+            .map(|n| self.parse(n, &expected))
+            .collect()
+    }
+
+    /// Parses each comma separated item of `part`, a slice of this line, trimmed of whitespace.
+    pub fn list<T: FromStr>(
+        &self,
+        part: &'a str,
+        expected: impl Display,
+    ) -> Result<Vec<T>, ParseError> {
+        part.split(',')
+            .map(|item| self.parse(item.trim(), &expected))
+            .collect()
+    }
+
+    /// Like [`Line::list`], for exactly `N` items such as the `x,y,z` of a position.
+    pub fn tuple<T: FromStr, const N: usize>(
+        &self,
+        part: &'a str,
+        expected: impl Display,
+    ) -> Result<[T; N], ParseError> {
+        let items = self.list::<T>(part, expected)?;
+        items
+            .try_into()
+            .map_err(|_| self.error_at(part, format!("{N} comma separated values")))
+    }
+
+    /// Splits an `a -> b, c` line into its source and destinations.
+    pub fn adjacency(&self) -> Result<(&'a str, Vec<&'a str>), ParseError> {
+        let (src, dsts) = self.split_once(self.text, " -> ", "`<source> -> <destinations>`")?;
+        Ok((src, dsts.split(',').map(|d| d.trim()).collect()))
+    }
+}
@@ -0,0 +1,232 @@
+//! Pictures of a day's puzzle state, written as PPM, PNG or SVG images.
+//!
+//! Days draw a [`Grid`] of colours picked from a [`Palette`], and [`encode`] turns that into an
+//! image file with each cell scaled up to a square of pixels.
+
+use std::path::Path;
+
+use crate::grid::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// The colours a day draws with, by the part they play in the picture rather than what they are.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub name: &'static str,
+    /// Empty space, and anything not worth drawing attention to.
+    pub background: Rgb,
+    /// The thing the puzzle is about: a loop, beams, a path or a trench.
+    pub highlight: Rgb,
+    /// Fixtures the highlight interacts with, like mirrors or rocks.
+    pub feature: Rgb,
+    /// Area enclosed by the highlight.
+    pub inside: Rgb,
+    /// Area shut out by the highlight.
+    pub outside: Rgb,
+    /// The ends of a scale of values, like heat loss, from least to most.
+    pub low: Rgb,
+    pub high: Rgb,
+}
+
+pub const PALETTES: &[Palette] = &[
+    Palette {
+        name: "dark",
+        background: Rgb(24, 24, 32),
+        highlight: Rgb(255, 204, 0),
+        feature: Rgb(200, 200, 210),
+        inside: Rgb(46, 139, 87),
+        outside: Rgb(40, 56, 104),
+        low: Rgb(16, 16, 64),
+        high: Rgb(255, 96, 32),
+    },
+    Palette {
+        name: "light",
+        background: Rgb(250, 250, 245),
+        highlight: Rgb(200, 30, 30),
+        feature: Rgb(60, 60, 60),
+        inside: Rgb(170, 220, 170),
+        outside: Rgb(200, 215, 235),
+        low: Rgb(255, 255, 224),
+        high: Rgb(140, 40, 20),
+    },
+    Palette {
+        name: "mono",
+        background: Rgb(0, 0, 0),
+        highlight: Rgb(255, 255, 255),
+        feature: Rgb(128, 128, 128),
+        inside: Rgb(176, 176, 176),
+        outside: Rgb(64, 64, 64),
+        low: Rgb(0, 0, 0),
+        high: Rgb(255, 255, 255),
+    },
+];
+
+impl Palette {
+    pub fn named(name: &str) -> Option<&'static Palette> {
+        PALETTES.iter().find(|p| p.name == name)
+    }
+
+    /// The colour for `value` on a scale from 0 to `max`, between `low` and `high`.
+    pub fn heat(&self, value: usize, max: usize) -> Rgb {
+        let t = if max == 0 {
+            0.0
+        } else {
+            value.min(max) as f64 / max as f64
+        };
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(
+            mix(self.low.0, self.high.0),
+            mix(self.low.1, self.high.1),
+            mix(self.low.2, self.high.2),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// The format named by `path`'s extension.
+    pub fn of_path(path: &Path) -> Option<ImageFormat> {
+        match path.extension()?.to_str()? {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "svg" => Some(ImageFormat::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// `image` as a file in `format`, with each cell drawn as a `scale` by `scale` square.
+pub fn encode(image: &Grid<Rgb>, scale: usize, format: ImageFormat) -> Vec<u8> {
+    assert!(scale > 0);
+    match format {
+        ImageFormat::Ppm => ppm(image, scale),
+        ImageFormat::Png => png(image, scale),
+        ImageFormat::Svg => svg(image, scale).into_bytes(),
+    }
+}
+
+/// Each row of pixels, as RGB bytes.
+fn pixel_rows(image: &Grid<Rgb>, scale: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    image.rows().flat_map(move |row| {
+        let bytes = row
+            .iter()
+            .flat_map(|c| std::iter::repeat_n([c.0, c.1, c.2], scale).flatten())
+            .collect::<Vec<_>>();
+        std::iter::repeat_n(bytes, scale)
+    })
+}
+
+fn ppm(image: &Grid<Rgb>, scale: usize) -> Vec<u8> {
+    let (width, height) = (image.width() * scale, image.height() * scale);
+    let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+    for row in pixel_rows(image, scale) {
+        out.extend(row);
+    }
+    out
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for x in chunk {
+            a += *x as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// A PNG with the image data stored uncompressed, which keeps this free of a deflate encoder.
+fn png(image: &Grid<Rgb>, scale: usize) -> Vec<u8> {
+    let (width, height) = (image.width() * scale, image.height() * scale);
+    let mut raw = Vec::<u8>::new();
+    for row in pixel_rows(image, scale) {
+        // Filter type 0: the row as is
+        raw.push(0);
+        raw.extend(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(u16::MAX as usize).collect::<Vec<_>>();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push((i == blocks.len() - 1) as u8);
+        let len = block.len() as u16;
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(*block);
+    }
+    if blocks.is_empty() {
+        zlib.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::<u8>::new();
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, default compression, filtering and no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut out, b"IHDR", &header);
+    png_chunk(&mut out, b"IDAT", &zlib);
+    png_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// An SVG with one rectangle per run of same coloured cells in a row.
+fn svg(image: &Grid<Rgb>, scale: usize) -> String {
+    let (width, height) = (image.width() * scale, image.height() * scale);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         shape-rendering=\"crispEdges\">\n"
+    );
+    for (y, row) in image.rows().enumerate() {
+        let mut x = 0;
+        for run in row.chunk_by(|a, b| a == b) {
+            let Rgb(r, g, b) = run[0];
+            out += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{scale}\" \
+                 fill=\"#{r:02x}{g:02x}{b:02x}\"/>\n",
+                x * scale,
+                y * scale,
+                run.len() * scale
+            );
+            x += run.len();
+        }
+    }
+    out += "</svg>\n";
+    out
+}
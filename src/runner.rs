@@ -0,0 +1,293 @@
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use crate::days::{Comparison, GenerateFn, ParseFn};
+use crate::generate::Rng;
+use crate::parse::ParseError;
+
+/// The answers from running a day against one input.
+///
+/// Each part is either its answer or the message it panicked with, or `None` if it wasn't run.
+#[derive(Debug, Clone)]
+pub struct Answers {
+    pub part1: Option<Result<String, String>>,
+    pub part2: Option<Result<String, String>>,
+}
+
+impl Answers {
+    /// Whether every part that ran got an answer.
+    pub fn is_ok(&self) -> bool {
+        self.part1.iter().chain(&self.part2).all(|p| p.is_ok())
+    }
+}
+
+/// Which of a day's parts to run. The parts only share the parsed input, so either can be run
+/// without paying for the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parts {
+    Both,
+    Part1,
+    Part2,
+}
+
+impl Parts {
+    /// Just `part`, or both if that's `None`.
+    pub fn only(part: Option<u8>) -> Parts {
+        match part {
+            None => Parts::Both,
+            Some(1) => Parts::Part1,
+            Some(2) => Parts::Part2,
+            Some(n) => panic!("there is no part {n}"),
+        }
+    }
+
+    pub fn includes(self, part: u8) -> bool {
+        match self {
+            Parts::Both => true,
+            Parts::Part1 => part == 1,
+            Parts::Part2 => part == 2,
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panicked".to_owned()
+    }
+}
+
+thread_local! {
+    /// Whether this thread is inside [`catch`], so its panics are reported by the caller.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// How many threads are inside [`catch`], whose days' parallel loops may panic on rayon's threads.
+static CATCHING_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs `f`, catching any panic as its message. The default hook's message and backtrace are left
+/// out, as the caller reports the panic in a line of its own.
+fn catch<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    static QUIET_HOOK: Once = Once::new();
+    QUIET_HOOK.call_once(|| {
+        let default = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let caught = CATCHING.get()
+                || (rayon::current_thread_index().is_some()
+                    && CATCHING_THREADS.load(Ordering::Relaxed) > 0);
+            if !caught {
+                default(info);
+            }
+        }));
+    });
+
+    let nested = CATCHING.replace(true);
+    if !nested {
+        CATCHING_THREADS.fetch_add(1, Ordering::Relaxed);
+    }
+    let result = catch_unwind(AssertUnwindSafe(f));
+    if !nested {
+        CATCHING_THREADS.fetch_sub(1, Ordering::Relaxed);
+        CATCHING.set(false);
+    }
+    result.map_err(panic_message)
+}
+
+/// Why an input couldn't be parsed, in which case neither part runs.
+#[derive(Debug, Clone)]
+pub enum ParseFailure {
+    Error(ParseError),
+    Panic(String),
+}
+
+impl ParseFailure {
+    pub fn in_file(self, day: &str, file: &Path) -> ParseFailure {
+        match self {
+            ParseFailure::Error(e) => ParseFailure::Error(e.in_file(day, file)),
+            ParseFailure::Panic(msg) => ParseFailure::Panic(msg),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseFailure::Error(e) => write!(f, "{e}"),
+            ParseFailure::Panic(msg) => write!(f, "parsing panicked: {msg}"),
+        }
+    }
+}
+
+/// How long parsing and each part took in a single run, with no time for parts that weren't run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+/// The min, median and max of a set of durations.
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn of(durations: &[Duration]) -> Stats {
+        assert!(!durations.is_empty());
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        Stats {
+            min: sorted[0],
+            median,
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// The answers from the last of one or more runs of a day, with the timings of every run.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub answers: Answers,
+    pub timings: Vec<Timings>,
+}
+
+impl Report {
+    pub fn parse_stats(&self) -> Stats {
+        Stats::of(&self.timings.iter().map(|t| t.parse).collect::<Vec<_>>())
+    }
+
+    pub fn part1_stats(&self) -> Stats {
+        Stats::of(&self.timings.iter().map(|t| t.part1).collect::<Vec<_>>())
+    }
+
+    pub fn part2_stats(&self) -> Stats {
+        Stats::of(&self.timings.iter().map(|t| t.part2).collect::<Vec<_>>())
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let v = f();
+    (v, start.elapsed())
+}
+
+/// Parses `input` and runs the chosen parts, catching any panic so one failing day doesn't stop
+/// the rest.
+pub fn run(parse: ParseFn, input: &str, parts: Parts) -> Result<(Answers, Timings), ParseFailure> {
+    let mut timings = Timings::default();
+
+    let (parsed, parse_time) = timed(|| catch(|| parse(input)));
+    timings.parse = parse_time;
+    let parsed = match parsed {
+        Ok(Ok(parsed)) => parsed,
+        Ok(Err(e)) => return Err(ParseFailure::Error(e)),
+        Err(msg) => return Err(ParseFailure::Panic(msg)),
+    };
+
+    let mut answers = Answers {
+        part1: None,
+        part2: None,
+    };
+    if parts.includes(1) {
+        let (part1, part1_time) = timed(|| catch(|| parsed.part1()));
+        answers.part1 = Some(part1);
+        timings.part1 = part1_time;
+    }
+    if parts.includes(2) {
+        let (part2, part2_time) = timed(|| catch(|| parsed.part2()));
+        answers.part2 = Some(part2);
+        timings.part2 = part2_time;
+    }
+    Ok((answers, timings))
+}
+
+/// Runs a day `repeat` times, keeping the timings of every run.
+pub fn run_repeated(
+    parse: ParseFn,
+    input: &str,
+    parts: Parts,
+    repeat: usize,
+) -> Result<Report, ParseFailure> {
+    assert!(repeat > 0);
+    let mut timings = Vec::<Timings>::new();
+    let mut answers = None;
+    for _ in 0..repeat {
+        let (a, t) = run(parse, input, parts)?;
+        timings.push(t);
+        answers = Some(a);
+    }
+    Ok(Report {
+        answers: answers.unwrap(),
+        timings,
+    })
+}
+
+/// Parses `input` and compares the day's shortcuts against its reference solvers, catching any
+/// panic.
+pub fn cross_check(parse: ParseFn, input: &str) -> Result<Vec<Comparison>, String> {
+    let parsed = catch(|| parse(input))?.map_err(|e| e.to_string())?;
+    catch(|| parsed.cross_check())
+}
+
+/// An answer that disagreed with its reference solver, or a failure to compare, on the input
+/// generated from `seed`.
+#[derive(Debug, Clone)]
+pub struct Disagreement {
+    pub seed: u64,
+    pub what: String,
+}
+
+/// How a day's shortcuts fared against its reference solvers over a run of generated inputs.
+#[derive(Debug, Clone, Default)]
+pub struct CrossChecks {
+    pub compared: usize,
+    /// How many of the compared answers were too slow to get from the reference solver.
+    pub skipped: usize,
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// Cross-checks a day on the inputs of about `size` that `generate` makes from seeds `0..seeds`.
+pub fn cross_check_seeds(
+    parse: ParseFn,
+    generate: GenerateFn,
+    size: usize,
+    seeds: u64,
+) -> CrossChecks {
+    let mut checks = CrossChecks::default();
+    for seed in 0..seeds {
+        let input = generate(&mut Rng::new(seed), Some(size));
+        match cross_check(parse, &input) {
+            Ok(comparisons) => {
+                for c in comparisons {
+                    checks.compared += 1;
+                    if c.reference.is_none() {
+                        checks.skipped += 1;
+                    } else if !c.agrees() {
+                        checks.disagreements.push(Disagreement {
+                            seed,
+                            what: c.to_string(),
+                        });
+                    }
+                }
+            }
+            Err(what) => checks.disagreements.push(Disagreement { seed, what }),
+        }
+    }
+    checks
+}
@@ -0,0 +1,86 @@
+//! Starting points for a new day: its module, input files and answers.
+//!
+//! The module needs no registering, as `build.rs` picks up every `days/dayN.rs` and
+//! `days/<year>/dayN.rs`, and the answers file is its test stub: `tests/inputs.rs` gets a test for
+//! each input with one.
+
+use std::path::{Path, PathBuf};
+
+use crate::days::{DayId, DEFAULT_YEAR};
+use crate::inputs;
+
+/// The module for a new day, with `DAYN` standing in for its number. Parsing splits the input into
+/// lines as most days start out doing, and everything else is left to fill in.
+const TEMPLATE: &str = r#"use super::Solution;
+use crate::generate::Rng;
+use crate::parse::{self, ParseError};
+
+pub struct DayDAYN;
+
+impl Solution for DayDAYN {
+    type Input = Vec<String>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        Ok(parse::lines(input).map(|l| l.text.to_owned()).collect())
+    }
+
+    fn part1(_lines: &Vec<String>) -> usize {
+        todo!()
+    }
+
+    fn part2(_lines: &Vec<String>) -> usize {
+        todo!()
+    }
+
+    fn generate(_rng: &mut Rng, _size: Option<usize>) -> String {
+        todo!()
+    }
+}
+"#;
+
+/// Placeholders that keep the example's test failing until they're replaced with the answers
+/// from the puzzle text.
+const ANSWERS: &str = "part1: ?\npart2: ?\n";
+
+/// Where the module for `day` lives under `sources`, in a directory for its year unless it's from
+/// [`DEFAULT_YEAR`].
+pub fn source_path(sources: &Path, day: DayId) -> PathBuf {
+    let dir = if day.year == DEFAULT_YEAR {
+        sources.to_path_buf()
+    } else {
+        sources.join(day.year.to_string())
+    };
+    dir.join(format!("{}.rs", day.name()))
+}
+
+/// Writes the module for `day` under `sources`, and its empty inputs and answers stub under
+/// `inputs`, returning the files made. Nothing is written if any of them already exist.
+pub fn new_day(sources: &Path, inputs: &Path, day: DayId) -> Result<Vec<PathBuf>, String> {
+    let source = source_path(sources, day);
+    let example = inputs::input_path(inputs, day, "example");
+    let files = [
+        (
+            source.clone(),
+            TEMPLATE.replace("DAYN", &day.day.to_string()),
+        ),
+        (example.clone(), String::new()),
+        (crate::answers::answers_path(&example), ANSWERS.to_owned()),
+        (inputs::input_path(inputs, day, "real"), String::new()),
+    ];
+    if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(format!("{} already exists", path.display()));
+    }
+
+    for dir in [
+        source.parent().unwrap().to_path_buf(),
+        inputs::day_dir(inputs, day),
+    ] {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    for (path, contents) in &files {
+        std::fs::write(path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
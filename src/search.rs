@@ -0,0 +1,211 @@
+//! Graph searches over any state type, for puzzles that are a walk through states rather than
+//! anything with a closed form.
+
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+/// A path found by a search, from the start to the goal with both ends included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<S> {
+    pub path: Vec<S>,
+    pub cost: usize,
+}
+
+/// The cheapest path from `start` to a state satisfying `is_goal`, or `None` if there isn't one.
+///
+/// `cost` is the cost of moving from one state to a neighbour of it. `heuristic` estimates the
+/// cost from a state to the nearest goal and must never overestimate it, or the path found may
+/// not be the cheapest.
+pub fn astar<S, I>(
+    start: S,
+    mut is_goal: impl FnMut(&S) -> bool,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut cost: impl FnMut(&S, &S) -> usize,
+    mut heuristic: impl FnMut(&S) -> usize,
+) -> Option<Found<S>>
+where
+    S: Clone + Eq + Hash + Ord,
+    I: IntoIterator<Item = S>,
+{
+    let mut came_from = HashMap::<S, S>::new();
+    let mut g_score = HashMap::<S, usize>::new();
+    let mut f_score = HashMap::<S, usize>::new();
+    let mut open_set = BTreeSet::<(usize, S)>::new();
+
+    let f = heuristic(&start);
+    g_score.insert(start.clone(), 0);
+    f_score.insert(start.clone(), f);
+    open_set.insert((f, start));
+
+    while let Some((_, current)) = open_set.pop_first() {
+        let g_score_cur = g_score[&current];
+        if is_goal(&current) {
+            let mut path = vec![current];
+            while let Some(from) = came_from.get(path.last().unwrap()) {
+                path.push(from.clone());
+            }
+            path.reverse();
+            return Some(Found {
+                path,
+                cost: g_score_cur,
+            });
+        }
+
+        for next in neighbours(&current) {
+            let tentative_g_score = g_score_cur + cost(&current, &next);
+            if tentative_g_score >= *g_score.get(&next).unwrap_or(&usize::MAX) {
+                continue;
+            }
+            came_from.insert(next.clone(), current.clone());
+            g_score.insert(next.clone(), tentative_g_score);
+            let f = tentative_g_score + heuristic(&next);
+            if let Some(old_f) = f_score.insert(next.clone(), f) {
+                open_set.remove(&(old_f, next.clone()));
+            }
+            open_set.insert((f, next));
+        }
+    }
+    None
+}
+
+/// [`astar`] without a heuristic.
+pub fn dijkstra<S, I>(
+    start: S,
+    is_goal: impl FnMut(&S) -> bool,
+    neighbours: impl FnMut(&S) -> I,
+    cost: impl FnMut(&S, &S) -> usize,
+) -> Option<Found<S>>
+where
+    S: Clone + Eq + Hash + Ord,
+    I: IntoIterator<Item = S>,
+{
+    astar(start, is_goal, neighbours, cost, |_| 0)
+}
+
+/// Every state reachable from `start`, with the fewest steps it takes to get there and the state
+/// it was first reached from.
+pub fn breadth_first<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+) -> HashMap<S, (usize, Option<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut reached = HashMap::<S, (usize, Option<S>)>::new();
+    reached.insert(start.clone(), (0, None));
+    let mut frontier = vec![start];
+    let mut next_frontier = Vec::<S>::new();
+    let mut steps = 0usize;
+    while !frontier.is_empty() {
+        steps += 1;
+        for s in frontier.drain(..) {
+            for next in neighbours(&s) {
+                if !reached.contains_key(&next) {
+                    reached.insert(next.clone(), (steps, Some(s.clone())));
+                    next_frontier.push(next);
+                }
+            }
+        }
+        frontier.append(&mut next_frontier);
+    }
+    reached
+}
+
+/// The path from the start of a [`breadth_first`] search to `to`, with both ends included.
+pub fn path_to<S>(reached: &HashMap<S, (usize, Option<S>)>, to: &S) -> Vec<S>
+where
+    S: Clone + Eq + Hash,
+{
+    let mut path = vec![to.clone()];
+    while let Some((_, Some(from))) = reached.get(path.last().unwrap()) {
+        path.push(from.clone());
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Directed edges with their costs. The cheapest way from 0 to 3 goes through every node but
+    /// 4, which nothing leads to.
+    const EDGES: &[(u32, u32, usize)] = &[
+        (0, 1, 1),
+        (0, 2, 4),
+        (1, 2, 1),
+        (1, 3, 5),
+        (2, 3, 1),
+        (4, 0, 1),
+    ];
+
+    fn neighbours(s: &u32) -> Vec<u32> {
+        EDGES.iter().filter(|e| e.0 == *s).map(|e| e.1).collect()
+    }
+
+    fn cost(from: &u32, to: &u32) -> usize {
+        EDGES.iter().find(|e| e.0 == *from && e.1 == *to).unwrap().2
+    }
+
+    /// The cheapest cost from each node to 3, which is as good as a heuristic gets.
+    fn heuristic(s: &u32) -> usize {
+        [3, 2, 1, 0, 4][*s as usize]
+    }
+
+    #[test]
+    fn astar_finds_the_cheapest_path() {
+        let found = astar(0, |s| *s == 3, neighbours, cost, heuristic);
+        assert_eq!(
+            found,
+            Some(Found {
+                path: vec![0, 1, 2, 3],
+                cost: 3
+            })
+        );
+    }
+
+    #[test]
+    fn dijkstra_finds_the_cheapest_path() {
+        let found = dijkstra(0, |s| *s == 3, neighbours, cost);
+        assert_eq!(
+            found,
+            Some(Found {
+                path: vec![0, 1, 2, 3],
+                cost: 3
+            })
+        );
+    }
+
+    #[test]
+    fn start_is_a_path_to_itself() {
+        let found = dijkstra(2, |s| *s == 2, neighbours, cost);
+        assert_eq!(
+            found,
+            Some(Found {
+                path: vec![2],
+                cost: 0
+            })
+        );
+    }
+
+    #[test]
+    fn unreachable_goals_have_no_path() {
+        assert_eq!(astar(0, |s| *s == 4, neighbours, cost, heuristic), None);
+        assert_eq!(dijkstra(0, |s| *s == 4, neighbours, cost), None);
+    }
+
+    #[test]
+    fn breadth_first_counts_steps_not_costs() {
+        let reached = breadth_first(0, neighbours);
+        assert_eq!(reached[&0], (0, None));
+        assert_eq!(reached[&1], (1, Some(0)));
+        assert_eq!(reached[&2], (1, Some(0)));
+        // Reached first from 1, as it's the first of 0's neighbours
+        assert_eq!(reached[&3], (2, Some(1)));
+        assert!(!reached.contains_key(&4));
+
+        assert_eq!(path_to(&reached, &3), vec![0, 1, 3]);
+        assert_eq!(path_to(&reached, &0), vec![0]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Step by step recordings of the simulating days, for watching what they get up to.
+//!
+//! Days call [`record`] from inside their loops. That does nothing unless a trace has been
+//! [`start`]ed, so the hooks cost a single check in normal runs.
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::grid::Grid;
+use crate::render::{Palette, Rgb};
+
+/// One step of a simulation.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// What step this is, like `cycle 3, north`.
+    pub label: String,
+    pub text: String,
+    pub picture: Option<Grid<Rgb>>,
+}
+
+impl Frame {
+    pub fn new(label: impl Display, text: impl Display) -> Frame {
+        Frame {
+            label: label.to_string(),
+            text: text.to_string(),
+            picture: None,
+        }
+    }
+
+    /// A frame showing `grid` as its text, and as a picture coloured by `colour`.
+    pub fn of_grid<T: Display>(
+        label: impl Display,
+        grid: &Grid<T>,
+        colour: impl FnMut(&T) -> Rgb,
+    ) -> Frame {
+        Frame {
+            picture: Some(grid.map(colour)),
+            ..Frame::new(label, grid)
+        }
+    }
+}
+
+/// The frames recorded so far, and how many were left out for going over the limit.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub frames: Vec<Frame>,
+    pub dropped: usize,
+}
+
+struct Recorder {
+    palette: &'static Palette,
+    limit: usize,
+    trace: Trace,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+fn recorder() -> MutexGuard<'static, Option<Recorder>> {
+    // A day panicking mid-frame shouldn't stop the frames before it being played back
+    RECORDER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Starts recording up to `limit` frames, with pictures drawn in `palette`.
+pub fn start(palette: &'static Palette, limit: usize) {
+    *recorder() = Some(Recorder {
+        palette,
+        limit,
+        trace: Trace {
+            frames: Vec::new(),
+            dropped: 0,
+        },
+    });
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stops recording and returns what was recorded, if anything was being.
+pub fn finish() -> Option<Trace> {
+    ENABLED.store(false, Ordering::Relaxed);
+    recorder().take().map(|r| r.trace)
+}
+
+/// Whether a trace is being recorded.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records the frame made by `f`, if a trace is being recorded and has room for it.
+pub fn record(f: impl FnOnce(&Palette) -> Frame) {
+    if !enabled() {
+        return;
+    }
+    let mut recorder = recorder();
+    let Some(r) = recorder.as_mut() else {
+        return;
+    };
+    if r.trace.frames.len() >= r.limit {
+        r.trace.dropped += 1;
+        return;
+    }
+    let frame = f(r.palette);
+    r.trace.frames.push(frame);
+}
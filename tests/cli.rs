@@ -0,0 +1,257 @@
+//! The command line, with the global flags before and after the subcommands.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Every global flag, with a value where it takes one.
+const GLOBAL_FLAGS: &[&[&str]] = &[
+    &["--inputs", "inputs"],
+    &["--repeat", "2"],
+    &["--format", "json"],
+    &["-v"],
+    &["--log", "day6"],
+    &["--year", "2023"],
+    &["--part", "1"],
+];
+
+const SUBCOMMANDS: &[&str] = &[
+    "all",
+    "verify",
+    "render",
+    "generate",
+    "differential",
+    "new",
+    "watch",
+];
+
+fn aoc23(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc23"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn global_flags_before_subcommands() {
+    // Asking for help shows which command the arguments were parsed as, without running it
+    for flag in GLOBAL_FLAGS {
+        for subcommand in SUBCOMMANDS {
+            let args = [*flag, &[subcommand, "--help"]].concat();
+            let output = aoc23(&args);
+            assert!(
+                stdout(&output).contains(&format!("Usage: aoc23 {subcommand}")),
+                "`aoc23 {}` wasn't parsed as {subcommand}: {}{}",
+                args.join(" "),
+                stdout(&output),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+}
+
+#[test]
+fn global_flags_after_subcommands() {
+    for flag in GLOBAL_FLAGS {
+        for subcommand in SUBCOMMANDS {
+            let args = [&[*subcommand], *flag, &["--help"]].concat();
+            let output = aoc23(&args);
+            assert!(
+                stdout(&output).contains(&format!("Usage: aoc23 {subcommand}")),
+                "`aoc23 {}` wasn't parsed as {subcommand}",
+                args.join(" ")
+            );
+        }
+    }
+}
+
+#[test]
+fn flags_before_a_subcommand_apply_to_it() {
+    let output = aoc23(&["--year", "2023", "--part", "1", "verify", "day6"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("2023/day6/example: ok"));
+
+    let output = aoc23(&["--format", "json", "--part", "2", "day6", "example"]);
+    assert!(output.status.success());
+    let json = stdout(&output);
+    assert!(json.contains("\"part1\":null"), "{json}");
+    assert!(json.contains("\"answer\":\"71503\""), "{json}");
+}
+
+#[test]
+fn runs_a_day_with_or_without_its_year() {
+    for args in [
+        &["day6", "example"][..],
+        &["6", "example"],
+        &["2023", "6", "example"],
+        &["--repeat", "2", "2023", "6", "example"],
+    ] {
+        let output = aoc23(args);
+        assert!(output.status.success(), "aoc23 {}", args.join(" "));
+        assert!(stdout(&output).contains("First: 288"));
+    }
+}
+
+#[test]
+fn rejects_a_subcommand_after_a_day() {
+    let output = aoc23(&["day6", "example", "all"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn year_before_a_subcommand_names_its_day() {
+    let output = aoc23(&["--year", "2023", "new", "6"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2023/day6 already exists"), "{stderr}");
+
+    let output = aoc23(&["--year", "1999", "generate", "6"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown day 1999/day6"), "{stderr}");
+}
+
+fn aoc23_with_stdin(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aoc23"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn grids_without_their_start_are_parse_errors() {
+    for (day, input, expected) in [
+        (
+            "day10",
+            "...\n.F7\n.LJ\n",
+            "2023/day10: <stdin>: expected one `S`, found none",
+        ),
+        (
+            "day10",
+            "S..\n.F7\n.LJ\n",
+            "2023/day10: <stdin>:1:1: expected `S` on a loop of pipes",
+        ),
+        (
+            "day21",
+            ".S.\n.#.\n.S.\n",
+            "2023/day21: <stdin>:3:2: expected only one `S`",
+        ),
+        (
+            "day23",
+            "#.#\n###\n",
+            "2023/day23: <stdin>:2:1: expected a `.` in the bottom row",
+        ),
+        (
+            "day17",
+            "",
+            "2023/day17: <stdin>:1:1: expected a row of cells",
+        ),
+    ] {
+        let output = aoc23_with_stdin(&[day, "-"], input);
+        let all = stdout(&output) + &String::from_utf8_lossy(&output.stderr);
+        assert!(all.contains(expected), "{day} on {input:?}: {all}");
+        assert!(!all.contains("panicked"), "{day} on {input:?}: {all}");
+    }
+}
+
+#[test]
+fn inputs_the_parts_cant_solve_are_parse_errors() {
+    for (day, input, expected) in [
+        (
+            "day5",
+            "seeds: 1 2\n\na-to-b map:\n5 0 10\n5 5 3\n",
+            "<stdin>:5:1: expected a source range clear of line 4",
+        ),
+        (
+            "day6",
+            "Time:\nDistance:\n",
+            "<stdin>:1:6: expected a number",
+        ),
+        (
+            "day8",
+            "LR\nx\nAAA = (AAA, AAA)\n",
+            "<stdin>:2:1: expected a blank line",
+        ),
+        (
+            "day12",
+            "??.# 1,0\n",
+            "<stdin>:1:8: expected a group size from 1 to 4",
+        ),
+        (
+            "day13",
+            "#.#\n.##\n##.\n",
+            "<stdin>:1:1: expected a pattern with one line of reflection",
+        ),
+        ("day14", "", "<stdin>:1:1: expected a row of cells"),
+        (
+            "day15",
+            "rn=1,cm,qp=3\n",
+            "<stdin>:1:6: expected `<label>=<focal length>` or `<label>-`",
+        ),
+        ("day16", "", "<stdin>:1:1: expected a row of cells"),
+        (
+            "day19",
+            "px{A}\n\n{x=1,m=1,a=1,s=1}\n",
+            "<stdin>: expected an `in` workflow",
+        ),
+        (
+            "day19",
+            "in{a<5:qq,R}\n",
+            "<stdin>:1:8: expected `A`, `R` or the name of a workflow",
+        ),
+        (
+            "day22",
+            "1,0,0~1,2,0\n",
+            "<stdin>:1:1: expected an end above the ground",
+        ),
+        (
+            "day22",
+            "2,0,1~1,0,1\n",
+            "<stdin>:1:7: expected an end no lower than `2,0,1`",
+        ),
+        (
+            "day22",
+            "0,0,1~0,2,1\n0,1,1~2,1,1\n",
+            "<stdin>:2:1: expected a brick clear of line 1",
+        ),
+        (
+            "day20",
+            "%a -> b\n",
+            "<stdin>: expected a `broadcaster` module",
+        ),
+        (
+            "day20",
+            "broadcaster -> a\nbutton -> a\n",
+            "<stdin>:2:1: expected a module name other than `button`",
+        ),
+    ] {
+        let output = aoc23_with_stdin(&[day, "-"], input);
+        let all = stdout(&output) + &String::from_utf8_lossy(&output.stderr);
+        assert!(all.contains(expected), "{day} on {input:?}: {all}");
+        assert!(!all.contains("panicked"), "{day} on {input:?}: {all}");
+    }
+}
+
+#[test]
+fn log_writes_only_the_days_it_names() {
+    let output = aoc23(&["--log", "day17", "all", "example", "--parallel"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[info] Best path loses 102"), "{stderr}");
+    assert!(!stderr.contains("Lagoon"), "{stderr}");
+    assert!(!stdout(&output).contains("[info]"));
+}
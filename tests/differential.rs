@@ -0,0 +1,26 @@
+//! The days' shortcuts checked against their brute force reference solvers, on small generated
+//! inputs.
+
+use aoc23::days::{DayId, CROSS_CHECKED, DAYS, GENERATORS};
+use aoc23::runner;
+
+const SEEDS: u64 = 5;
+
+fn check(day: DayId, size: usize) {
+    let parse = DAYS.iter().find(|d| d.0 == day).unwrap().1;
+    let generate = GENERATORS.iter().find(|g| g.0 == day).unwrap().1;
+    let checks = runner::cross_check_seeds(parse, generate, size, SEEDS);
+    assert!(checks.compared > 0, "{day} has no reference solver");
+    assert_eq!(checks.skipped, 0, "{day} was too slow to check");
+    if let Some(d) = checks.disagreements.first() {
+        panic!("{day} seed {}: {}", d.seed, d.what);
+    }
+}
+
+/// Every day the `differential` subcommand checks, at the same sizes.
+#[test]
+fn cross_checked_days() {
+    for (day, size) in CROSS_CHECKED {
+        check(*day, *size);
+    }
+}
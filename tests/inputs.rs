@@ -0,0 +1,25 @@
+//! One test per `inputs/<year>/<day>/<variant>.txt` that has a recorded `.answers` file,
+//! generated by `build.rs`.
+
+use std::path::Path;
+
+use aoc23::answers;
+use aoc23::days::{DayId, DAYS};
+use aoc23::inputs;
+use aoc23::runner::Parts;
+
+fn check(day: DayId, variant: &str) {
+    let parse = DAYS.iter().find(|d| d.0 == day).unwrap().1;
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs");
+    let path = inputs::input_path(&root, day, variant);
+    let verdict = answers::verify(day, parse, &path, Parts::Both)
+        .unwrap()
+        .unwrap();
+    assert!(
+        verdict.passed(),
+        "{day}/{variant}: {}",
+        verdict.problems.join("; ")
+    );
+}
+
+include!(concat!(env!("OUT_DIR"), "/input_tests.rs"));